@@ -11,3 +11,60 @@ pub const COWARDICE_COEFF: f64 = -1.1;
 
 /// The dijksra map fleeing coefficient for brave souls.
 pub const BRAVERY_COEFF: f64 = -1.6;
+
+/// The amount of gold in the town treasury at the start of the game.
+pub const STARTING_GOLD: usize = 500;
+
+/// The number of households living in town at the start of the game.
+pub const STARTING_HOUSEHOLDS: usize = 4;
+
+/// The default number of construction actions which can be undone.
+pub const UNDO_DEPTH: usize = 50;
+
+/// The number of turns in an in-world day.
+pub const TURNS_PER_DAY: usize = 240;
+
+/// The number of days in an in-world year.
+pub const DAYS_PER_YEAR: usize = 30;
+
+/// The number of hours in an in-world day.
+pub const HOURS_PER_DAY: usize = 24;
+
+/// The hour at which people get up.
+pub const WAKE_HOUR: usize = 6;
+
+/// The hour at which people go to bed.
+pub const SLEEP_HOUR: usize = 22;
+
+/// The hour at which work starts.
+pub const WORK_START_HOUR: usize = 9;
+
+/// The hour at which work ends.
+pub const WORK_END_HOUR: usize = 17;
+
+/// The difficulty of repairing a damaged static.
+pub const REPAIR_DIFFICULTY: usize = 20;
+
+/// The difficulty of finding anything worth having in the dungeon.
+pub const DELVE_DIFFICULTY: usize = 25;
+
+/// The difficulty of patching up wounds from the dungeon.
+pub const HEAL_DIFFICULTY: usize = 20;
+
+/// The price of a day's supplies for the dungeon.
+pub const SUPPLY_PRICE: usize = 5;
+
+/// The price a piece of loot sells for, if haggled over competently.
+pub const LOOT_PRICE: usize = 10;
+
+/// The gold at which a mob counts as rich.
+pub const RICH_GOLD: usize = 200;
+
+/// The number of turns a mob runs away for after a fright.
+pub const FLEE_TURNS: usize = 30;
+
+/// The file training packages are loaded from, if it exists.
+pub const TRAINING_PATH: &'static str = "training.txt";
+
+/// The training packages built into the game, used when there is no file to load them from.
+pub const DEFAULT_TRAINING: &'static str = include_str!("../training.txt");
@@ -209,6 +209,7 @@ impl Map {
                 let weight = self.approach.at(Point { x: x, y: y });
                 if weight < min_so_far {
                     new_pos = Some(Point { x: x, y: y });
+                    min_so_far = weight;
                 }
             }
         }
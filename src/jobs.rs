@@ -0,0 +1,285 @@
+//! Jobs: townsfolk working in the town's buildings. Each type of building needs people in certain
+//! roles, such as an innkeeper for an inn, and each role has a station (the counter they stand
+//! behind), a profession which makes someone good at it, and working hours.
+//!
+//! Jobs are handed out at the start of the game, whenever a building goes up or comes down, and at
+//! the start of each day: those who already have one keep it, and each vacancy goes to the
+//! unemployed townsperson whose profession best fits it. Each worker owns the station they work
+//! at, and gives it up when they lose the job. A worker is on duty when they are at their station
+//! during their working hours. Counters with someone on duty serve customers better; and some,
+//! like inn counters, don't serve anyone at all without someone behind them.
+
+use dijkstra_map::Maps;
+use grid::Point;
+use mobiles::Mobile;
+use mobiles::attributes::Attribute;
+use mobiles::behaviour::Archetype;
+use mobiles::schedule::Schedule;
+use statics::StaticTag;
+use std::collections::BTreeMap;
+use types::{Message, PlaceKind, World};
+
+/// The hour an inn opens for the day's trade.
+const INN_OPENING_HOUR: usize = 11;
+
+/// The hour an inn closes.
+const INN_CLOSING_HOUR: usize = 21;
+
+/// The hour a store opens.
+const STORE_OPENING_HOUR: usize = 9;
+
+/// The hour a store closes.
+const STORE_CLOSING_HOUR: usize = 17;
+
+/// The roles buildings need filling.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Role {
+    /// Runs an inn, serving food and drink from behind the counter.
+    Innkeeper,
+    /// Runs a store, serving customers from behind the counter.
+    Shopkeeper,
+}
+
+/// A position in a building, and who (if anyone) holds it.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Job {
+    /// The name of the building.
+    pub place: String,
+    /// The role.
+    pub role: Role,
+    /// Where the worker stands to work.
+    pub station: Point,
+    /// The id of the worker, if the job is filled.
+    pub worker: Option<usize>,
+    /// How good the worker is at the job: their value in the profession.
+    pub skill: usize,
+    /// Whether the worker is at their station.
+    pub on_duty: bool,
+}
+
+impl Role {
+    /// The name of the role.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Role::Innkeeper => "innkeeper",
+            Role::Shopkeeper => "shopkeeper",
+        }
+    }
+
+    /// The profession which makes someone good at the role.
+    pub fn profession(&self) -> Attribute {
+        match *self {
+            Role::Innkeeper => Attribute::ProfessionInnkeeper,
+            Role::Shopkeeper => Attribute::ProfessionTrader,
+        }
+    }
+
+    /// The static the worker works at.
+    pub fn station(&self) -> StaticTag {
+        match *self {
+            Role::Innkeeper => StaticTag::InnCounter,
+            Role::Shopkeeper => StaticTag::GStoreCounter,
+        }
+    }
+
+    /// The working hours, from the hour work starts up to (but not including) the hour it ends.
+    /// Inns keep later hours than stores, to catch the evening trade.
+    pub fn shift(&self) -> (usize, usize) {
+        match *self {
+            Role::Innkeeper => (INN_OPENING_HOUR, INN_CLOSING_HOUR),
+            Role::Shopkeeper => (STORE_OPENING_HOUR, STORE_CLOSING_HOUR),
+        }
+    }
+
+    /// Whether the worker eats at their station, rather than taking meal breaks. Innkeepers can't
+    /// close the inn at mealtimes, as that's when it's busiest.
+    pub fn eats_at_work(&self) -> bool {
+        match *self {
+            Role::Innkeeper => true,
+            Role::Shopkeeper => false,
+        }
+    }
+}
+
+impl World {
+    /// Hand out jobs. Every building gets a job for each role it needs, at the first of its
+    /// stations still standing. Workers keep their jobs if they're still around; the rest go to
+    /// unemployed townsfolk, the best at the profession first. Anyone whose job has gone (because
+    /// the building has, or they were only just given it) is updated to match.
+    pub fn assign_jobs(&mut self, maps: &mut Maps, mobs: &mut BTreeMap<Point, Mobile>) {
+        // Work out what jobs there are now, keeping on the old workers.
+        let mut jobs = Vec::new();
+        for place in &self.places {
+            let kind = match place.kind {
+                PlaceKind::Building(kind) => kind,
+                _ => continue,
+            };
+            for role in kind.roles() {
+                let station = match self.stations(&place.name, role).into_iter().next() {
+                    Some(station) => station,
+                    None => continue,
+                };
+                let mut job = Job {
+                    place: place.name.clone(),
+                    role: role,
+                    station: station,
+                    worker: None,
+                    skill: 0,
+                    on_duty: false,
+                };
+                let old = self.jobs.iter().find(|j| j.place == place.name && j.role == role);
+                if let Some(old) = old {
+                    if old.worker.map_or(false, |id| mobs.values().any(|m| m.id == id)) {
+                        job.worker = old.worker;
+                        job.skill = old.skill;
+                        job.on_duty = old.on_duty;
+                    }
+                }
+                jobs.push(job);
+            }
+        }
+
+        // Close any counters left without anyone on duty.
+        let old_jobs = ::std::mem::replace(&mut self.jobs, jobs);
+        for old in old_jobs.into_iter().filter(|j| j.on_duty) {
+            if !self.jobs.iter().any(|j| j.place == old.place && j.role == old.role && j.on_duty) {
+                self.open_stations(maps, &old, false);
+            }
+        }
+
+        // Fill the vacancies.
+        for i in 0..self.jobs.len() {
+            if self.jobs[i].worker.is_some() {
+                continue;
+            }
+            let role = self.jobs[i].role;
+            let hired = mobs.values()
+                .filter(|m| m.job.is_none() && m.archetype() == Archetype::Townsperson)
+                .max_by_key(|m| m.attributes.get(role.profession()))
+                .map(|m| (m.id, m.name.clone(), m.attributes.get(role.profession())));
+            if let Some((id, name, skill)) = hired {
+                self.jobs[i].worker = Some(id);
+                self.jobs[i].skill = skill;
+                for mob in mobs.values_mut().filter(|m| m.id == id) {
+                    mob.job = Some(role);
+                }
+                let msg = format!("{} has taken a job as the {} of {}.",
+                                  name,
+                                  role.name(),
+                                  self.jobs[i].place);
+                let loc = Some(self.jobs[i].station);
+                self.log(Message {
+                    msg: msg,
+                    loc: loc,
+                });
+            }
+        }
+
+        // Workers look after their stations.
+        for job in self.jobs.clone() {
+            self.set_owner(job.station, job.worker);
+        }
+
+        // Let everyone know where they're working, and when.
+        for mob in mobs.values_mut() {
+            let (job, workplace) = match self.jobs.iter().find(|j| j.worker == Some(mob.id)) {
+                Some(j) => (Some(j.role), Some(j.station)),
+                None => (None, None),
+            };
+            if mob.job != job || mob.workplace != workplace {
+                mob.job = job;
+                mob.workplace = workplace;
+                mob.schedule = Schedule::for_mob(mob);
+            }
+        }
+    }
+
+    /// Record whether a worker is at their station. When this changes, any stations which only
+    /// work with someone behind them are opened or closed.
+    pub fn set_on_duty(&mut self, maps: &mut Maps, worker: usize, on_duty: bool) {
+        let job = match self.jobs.iter_mut().find(|j| j.worker == Some(worker)) {
+            Some(job) => {
+                if job.on_duty == on_duty {
+                    return;
+                }
+                job.on_duty = on_duty;
+                job.clone()
+            }
+            None => return,
+        };
+        self.open_stations(maps, &job, on_duty);
+    }
+
+    /// A worker has gone for good: take them off duty, and leave their job open for someone else.
+    pub fn leave_job(&mut self, maps: &mut Maps, worker: usize) {
+        self.set_on_duty(maps, worker, false);
+        let mut stations = Vec::new();
+        for job in self.jobs.iter_mut().filter(|j| j.worker == Some(worker)) {
+            job.worker = None;
+            job.skill = 0;
+            stations.push(job.station);
+        }
+        for station in stations {
+            self.set_owner(station, None);
+        }
+    }
+
+    /// The job with someone on duty at the static at a point, if there is one.
+    pub fn staffed_job(&self, pos: Point) -> Option<&Job> {
+        let tag = match self.statics.at(pos) {
+            Some(s) => s.tag,
+            None => return None,
+        };
+        self.jobs.iter().find(|j| {
+            j.on_duty && j.role.station() == tag &&
+            self.places.iter().any(|p| p.name == j.place && p.contains(pos))
+        })
+    }
+
+    /// Whether there is someone on duty at the static at a point.
+    pub fn is_staffed(&self, pos: Point) -> bool {
+        self.staffed_job(pos).is_some()
+    }
+
+    /// The stations for a role in a building.
+    fn stations(&self, place: &str, role: Role) -> Vec<Point> {
+        let mut stations = Vec::new();
+        for p in self.places.iter().filter(|p| p.name == place) {
+            for y in p.top_left.y..p.bottom_right.y + 1 {
+                for x in p.top_left.x..p.bottom_right.x + 1 {
+                    let pos = Point { x: x, y: y };
+                    if self.statics.at(pos).map_or(false, |s| s.tag == role.station()) {
+                        stations.push(pos);
+                    }
+                }
+            }
+        }
+        stations
+    }
+
+    /// Change the owner of the static at a point, if there is one.
+    fn set_owner(&mut self, pos: Point, owner: Option<usize>) {
+        if let Some(mut s) = self.statics.at(pos) {
+            s.owner = owner;
+            self.statics.set(pos, Some(s));
+        }
+    }
+
+    /// Add the stations for a job which need staff to their heatmap, or take them off it.
+    fn open_stations(&mut self, maps: &mut Maps, job: &Job, open: bool) {
+        for pos in self.stations(&job.place, job.role) {
+            let tag = match (self.statics.at(pos), self.sources.get(&pos)) {
+                (Some(s), Some(&tag)) if s.needs_staff() => tag,
+                _ => continue,
+            };
+            let map = maps.mutget(tag);
+            if open {
+                if !map.sources.contains(&pos) {
+                    map.add_source(pos, self);
+                }
+            } else {
+                map.remove_source(pos, self);
+            }
+        }
+    }
+}
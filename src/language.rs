@@ -11,6 +11,22 @@ const EXTRA_MORPHEMES: usize = 10;
 /// The maximum character length of a given name.
 const MAX_GIVEN_LEN: usize = 12;
 
+/// The number of "extra" words to consider when picking one.
+const EXTRA_WORDS: usize = 3;
+
+/// The maximum character length of a word.
+const MAX_WORD_LEN: usize = 12;
+
+/// The minimum character length of a place or region name.
+const MIN_PLACE_LEN: usize = 4;
+
+/// The maximum character length of a place or region name.
+const MAX_PLACE_LEN: usize = 24;
+
+/// The number of attempts to make at generating a word or name within the length limit, before
+/// accepting whatever comes out.
+const MAX_ATTEMPTS: usize = 100;
+
 /// A language is a collection of morphemes, and rules for generating more morphemes.
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct Language {
@@ -43,6 +59,12 @@ pub struct Language {
     place_words: Vec<String>,
     /// Region names
     region_words: Vec<String>,
+    /// The word used to join two words as "x of y".
+    genitive: String,
+    /// The word used to prefix a name as "the x".
+    definite: String,
+    /// Place and region names which have been given out, so none is used twice.
+    names: Vec<String>,
 
     // Syllable / morpheme generation
     /// The available vowels
@@ -76,6 +98,15 @@ enum L {
     F,
 }
 
+/// Types of words
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum W {
+    /// Place word
+    P,
+    /// Region word
+    R,
+}
+
 /// Types of name components
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 enum N {
@@ -94,27 +125,7 @@ enum N {
 impl Language {
     /// Generate a random language.
     pub fn new<R: Rng>(rng: &mut R) -> Language {
-        let mut l = Language {
-            generic_morphemes: Vec::new(),
-            place_morphemes: Vec::new(),
-            region_morphemes: Vec::new(),
-            name_morphemes: Vec::new(),
-            particle_morphemes: Vec::new(),
-            per_given: (0, 0),
-            surname_structure: Vec::new(),
-            capitalise_particles: 0.0,
-            joiner: ' ',
-            per_word: (0, 0),
-            place_words: Vec::new(),
-            region_words: Vec::new(),
-            vowels: Vec::new(),
-            consonants: Vec::new(),
-            sibilants: Vec::new(),
-            liquids: Vec::new(),
-            finals: Vec::new(),
-            orthography: BTreeMap::new(),
-            syllable_structure: Vec::new(),
-        };
+        let mut l = Language::empty();
 
         // Letter sets
         l.vowels = match rng.gen_range(0, 7) {
@@ -276,12 +287,51 @@ impl Language {
         l.capitalise_particles = rng.next_f64();
         l.joiner = *rng.choose(&[' ', ' ', ' ', ' ', '-', '-', '\'']).unwrap();
 
+        // Words
+        l.genitive = l.gen_morpheme(rng).unwrap_or_default();
+        l.definite = l.gen_morpheme(rng).unwrap_or_default();
+
         l
     }
 
+    /// A language with no letters, morphemes, or structure. This is filled in by `new` or
+    /// `deserialize`.
+    fn empty() -> Language {
+        Language {
+            generic_morphemes: Vec::new(),
+            place_morphemes: Vec::new(),
+            region_morphemes: Vec::new(),
+            name_morphemes: Vec::new(),
+            particle_morphemes: Vec::new(),
+            per_given: (0, 0),
+            surname_structure: Vec::new(),
+            capitalise_particles: 0.0,
+            joiner: ' ',
+            per_word: (0, 0),
+            place_words: Vec::new(),
+            region_words: Vec::new(),
+            genitive: String::new(),
+            definite: String::new(),
+            names: Vec::new(),
+            vowels: Vec::new(),
+            consonants: Vec::new(),
+            sibilants: Vec::new(),
+            liquids: Vec::new(),
+            finals: Vec::new(),
+            orthography: BTreeMap::new(),
+            syllable_structure: Vec::new(),
+        }
+    }
+
     /// Generate a personal name.
     pub fn gen_personal<R: Rng>(&mut self, rng: &mut R) -> String {
-        // Given name
+        let given = self.gen_given(rng);
+        let surname = self.gen_surname(rng);
+        format!("{} {}", given, surname)
+    }
+
+    /// Generate a given name.
+    pub fn gen_given<R: Rng>(&mut self, rng: &mut R) -> String {
         let mut given = "".to_string();
         loop {
             given = "".to_string();
@@ -309,9 +359,12 @@ impl Language {
                 break;
             }
         }
-        given = capitalise_first(given);
+        capitalise_first(given)
+    }
 
-        // Surname
+    /// Generate a surname. This follows the surname structure of the language, so members of a
+    /// family can share one.
+    pub fn gen_surname<R: Rng>(&mut self, rng: &mut R) -> String {
         let mut surname = "".to_string();
         let mut particle = false;
         let mut first = true;
@@ -393,17 +446,280 @@ impl Language {
             surname += piece.as_str();
         }
 
-        format!("{} {}", given, surname)
+        surname
     }
 
     /// Generate a place name
     pub fn gen_place<R: Rng>(&mut self, rng: &mut R) -> String {
-        "".to_string()
+        self.gen_name(rng, W::P)
     }
 
     /// Generate a region name
     pub fn gen_region<R: Rng>(&mut self, rng: &mut R) -> String {
-        "".to_string()
+        self.gen_name(rng, W::R)
+    }
+
+    /// Save the language, including all the morphemes, words, and names it has come up with so
+    /// far, so it can be loaded again with `deserialize`.
+    ///
+    /// The format is one field per line: the field name followed by its values, all separated by
+    /// tabs. Syllable and surname structures are written as one letter per component, with a `?`
+    /// suffix if the component is optional.
+    pub fn serialize(&self) -> String {
+        let mut out = String::new();
+        field(&mut out, "generic", &self.generic_morphemes);
+        field(&mut out, "place", &self.place_morphemes);
+        field(&mut out, "region", &self.region_morphemes);
+        field(&mut out, "name", &self.name_morphemes);
+        field(&mut out, "particle", &self.particle_morphemes);
+        field(&mut out,
+              "per_given",
+              &[self.per_given.0.to_string(), self.per_given.1.to_string()]);
+        field(&mut out,
+              "surname",
+              &self.surname_structure
+                  .iter()
+                  .map(|&(n, req)| component(name_code(n), req))
+                  .collect::<Vec<_>>());
+        field(&mut out,
+              "capitalise_particles",
+              &[self.capitalise_particles.to_string()]);
+        field(&mut out, "joiner", &[self.joiner.to_string()]);
+        field(&mut out,
+              "per_word",
+              &[self.per_word.0.to_string(), self.per_word.1.to_string()]);
+        field(&mut out, "place_words", &self.place_words);
+        field(&mut out, "region_words", &self.region_words);
+        field(&mut out, "genitive", &[self.genitive.clone()]);
+        field(&mut out, "definite", &[self.definite.clone()]);
+        field(&mut out, "names", &self.names);
+        field(&mut out, "vowels", &letters(&self.vowels));
+        field(&mut out, "consonants", &letters(&self.consonants));
+        field(&mut out, "sibilants", &letters(&self.sibilants));
+        field(&mut out, "liquids", &letters(&self.liquids));
+        field(&mut out, "finals", &letters(&self.finals));
+        field(&mut out,
+              "orthography",
+              &self.orthography
+                  .iter()
+                  .map(|(c, s)| format!("{}={}", c, s))
+                  .collect::<Vec<_>>());
+        field(&mut out,
+              "syllable",
+              &self.syllable_structure
+                  .iter()
+                  .map(|&(l, req)| component(letter_code(l), req))
+                  .collect::<Vec<_>>());
+        out
+    }
+
+    /// Load a language saved by `serialize`.
+    pub fn deserialize(input: &str) -> Result<Language, String> {
+        let mut l = Language::empty();
+        for line in input.lines() {
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.split('\t');
+            let key = parts.next().unwrap_or("");
+            let vals: Vec<&str> = parts.collect();
+            let strings = || vals.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            match key {
+                "generic" => l.generic_morphemes = strings(),
+                "place" => l.place_morphemes = strings(),
+                "region" => l.region_morphemes = strings(),
+                "name" => l.name_morphemes = strings(),
+                "particle" => l.particle_morphemes = strings(),
+                "per_given" => l.per_given = try!(parse_range(key, &vals)),
+                "surname" => {
+                    l.surname_structure = try!(parse_components(key, &vals, parse_name_code))
+                }
+                "capitalise_particles" => {
+                    l.capitalise_particles = try!(parse_one(key, &vals)
+                        .and_then(|v| v.parse().map_err(|_| bad(key, v))))
+                }
+                "joiner" => l.joiner = try!(parse_letter(key, try!(parse_one(key, &vals)))),
+                "per_word" => l.per_word = try!(parse_range(key, &vals)),
+                "place_words" => l.place_words = strings(),
+                "region_words" => l.region_words = strings(),
+                "genitive" => l.genitive = try!(parse_one(key, &vals)).to_string(),
+                "definite" => l.definite = try!(parse_one(key, &vals)).to_string(),
+                "names" => l.names = strings(),
+                "vowels" => l.vowels = try!(parse_letters(key, &vals)),
+                "consonants" => l.consonants = try!(parse_letters(key, &vals)),
+                "sibilants" => l.sibilants = try!(parse_letters(key, &vals)),
+                "liquids" => l.liquids = try!(parse_letters(key, &vals)),
+                "finals" => l.finals = try!(parse_letters(key, &vals)),
+                "orthography" => {
+                    for v in vals {
+                        let mut kv = v.splitn(2, '=');
+                        let c = try!(parse_letter(key, kv.next().unwrap_or("")));
+                        let rom = try!(kv.next().ok_or_else(|| bad(key, v)));
+                        let _ = l.orthography.insert(c, rom.to_string());
+                    }
+                }
+                "syllable" => {
+                    l.syllable_structure = try!(parse_components(key, &vals, parse_letter_code))
+                }
+                _ => return Err(format!("Unknown field {:?}.", key)),
+            }
+        }
+
+        // A language needs letters to make morphemes from, and structures to put them together.
+        if l.vowels.is_empty() || l.consonants.is_empty() || l.sibilants.is_empty() ||
+           l.liquids.is_empty() || l.finals.is_empty() {
+            return Err("Missing a letter set.".to_string());
+        }
+        if l.syllable_structure.is_empty() || l.surname_structure.is_empty() {
+            return Err("Missing a syllable or surname structure.".to_string());
+        }
+        if l.per_given.0 == 0 || l.per_word.0 == 0 {
+            return Err("Missing a name or word length.".to_string());
+        }
+
+        Ok(l)
+    }
+
+    /// A human-readable summary of the phonology and structure of the language.
+    pub fn describe(&self) -> String {
+        let show = |cs: &Vec<char>| {
+            cs.iter()
+                .map(|c| self.romanize(*c))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut out = String::new();
+        out += format!("Vowels: {}\n", show(&self.vowels)).as_str();
+        out += format!("Consonants: {}\n", show(&self.consonants)).as_str();
+        out += format!("Sibilants: {}\n", show(&self.sibilants)).as_str();
+        out += format!("Liquids: {}\n", show(&self.liquids)).as_str();
+        out += format!("Finals: {}\n", show(&self.finals)).as_str();
+        out += format!("Syllables: {} (optional parts in brackets)\n",
+                       self.syllable_structure
+                           .iter()
+                           .map(|&(l, req)| if req {
+                               letter_code(l).to_string()
+                           } else {
+                               format!("({})", letter_code(l))
+                           })
+                           .collect::<String>())
+            .as_str();
+        out += format!("Morphemes per word: {} to {}\n",
+                       self.per_word.0,
+                       self.per_word.1)
+            .as_str();
+        out += format!("Morphemes per given name: {} to {}\n",
+                       self.per_given.0,
+                       self.per_given.1)
+            .as_str();
+        out += format!("Joiner: {:?}\n", self.joiner).as_str();
+        out += format!("\"Of\": {}\n", self.genitive).as_str();
+        out += format!("\"The\": {}\n", self.definite).as_str();
+        out += format!("Morphemes known: {}\n",
+                       self.generic_morphemes.len() + self.place_morphemes.len() +
+                       self.region_morphemes.len() +
+                       self.name_morphemes.len() +
+                       self.particle_morphemes.len())
+            .as_str();
+        out
+    }
+
+    /// Generate a place or region name. This is either a single word, two words, or two words
+    /// joined by the genitive ("x of y"); and is occasionally prefixed by the definite article.
+    fn gen_name<R: Rng>(&mut self, rng: &mut R, ty: W) -> String {
+        let joiner = self.joiner.to_string();
+        let mut name = "".to_string();
+        for _ in 0..MAX_ATTEMPTS {
+            name = if rng.gen() {
+                capitalise_first(self.pick_word(rng, ty))
+            } else {
+                let w1 = capitalise_first(self.pick_word(rng, ty));
+                let w2 = capitalise_first(self.pick_word(rng, ty));
+                if w1 == w2 {
+                    continue;
+                }
+                if rng.gen() {
+                    w1 + joiner.as_str() + w2.as_str()
+                } else {
+                    w1 + joiner.as_str() + self.genitive.as_str() + joiner.as_str() + w2.as_str()
+                }
+            };
+            if rng.gen_range(0, 10) == 0 {
+                name = capitalise_first(self.definite.clone()) + joiner.as_str() + name.as_str();
+            }
+
+            // Check the length limits and that it's not been used before
+            let len = name.chars().count();
+            if len >= MIN_PLACE_LEN && len <= MAX_PLACE_LEN && !self.names.contains(&name) {
+                break;
+            }
+        }
+
+        self.names.push(name.clone());
+        name
+    }
+
+    /// Pick a word, possibly generating a new one. Words are remembered, so the same ones will
+    /// crop up in different names.
+    fn pick_word<R: Rng>(&mut self, rng: &mut R, ty: W) -> String {
+        let num_words = match ty {
+            W::P => self.place_words.len(),
+            W::R => self.region_words.len(),
+        };
+        let i = rng.gen_range(0, num_words + EXTRA_WORDS);
+        if i < num_words {
+            return match ty {
+                W::P => self.place_words[i].clone(),
+                W::R => self.region_words[i].clone(),
+            };
+        }
+
+        let mut word = "".to_string();
+        for _ in 0..MAX_ATTEMPTS {
+            word = self.gen_word(rng, ty);
+            if !self.place_words.contains(&word) && !self.region_words.contains(&word) {
+                break;
+            }
+        }
+        match ty {
+            W::P => self.place_words.push(word.clone()),
+            W::R => self.region_words.push(word.clone()),
+        }
+        word
+    }
+
+    /// Generate a fresh word. One of the morphemes is a place or region morpheme, and the rest are
+    /// generic.
+    fn gen_word<R: Rng>(&mut self, rng: &mut R, ty: W) -> String {
+        let mut word = "".to_string();
+        for _ in 0..MAX_ATTEMPTS {
+            word = "".to_string();
+            let wlen = rng.gen_range(self.per_word.0, self.per_word.1 + 1);
+            let kidx = rng.gen_range(0, wlen);
+            for i in 0..wlen {
+                let (morph, new) = self.pick_morpheme(rng,
+                                                      match (i == kidx, ty) {
+                                                          (true, W::P) => &self.place_morphemes,
+                                                          (true, W::R) => &self.region_morphemes,
+                                                          (false, _) => &self.generic_morphemes,
+                                                      });
+                if new {
+                    match (i == kidx, ty) {
+                        (true, W::P) => self.place_morphemes.push(morph.clone()),
+                        (true, W::R) => self.region_morphemes.push(morph.clone()),
+                        (false, _) => self.generic_morphemes.push(morph.clone()),
+                    }
+                }
+                word += morph.as_str();
+            }
+
+            // Check the length limit
+            if word.chars().count() <= MAX_WORD_LEN {
+                break;
+            }
+        }
+
+        word
     }
 
     /// Pick a morpheme from a vector, possibly generating a new one. The return value `true`
@@ -443,7 +759,7 @@ impl Language {
             // Apply orthography
             let mut morph = "".to_string();
             for c in chs {
-                morph += self.orthography.get(&c).unwrap_or(&c.to_string()).as_str()
+                morph += self.romanize(*c).as_str()
             }
 
             // Check if it's a duplicate
@@ -461,6 +777,139 @@ impl Language {
 
         None
     }
+
+    /// The written form of a letter.
+    fn romanize(&self, c: char) -> String {
+        self.orthography.get(&c).cloned().unwrap_or(c.to_string())
+    }
+}
+
+/// The one-letter code for a type of letter.
+fn letter_code(l: L) -> char {
+    match l {
+        L::V => 'V',
+        L::C => 'C',
+        L::S => 'S',
+        L::L => 'L',
+        L::F => 'F',
+    }
+}
+
+/// The type of letter for a one-letter code.
+fn parse_letter_code(c: char) -> Option<L> {
+    match c {
+        'V' => Some(L::V),
+        'C' => Some(L::C),
+        'S' => Some(L::S),
+        'L' => Some(L::L),
+        'F' => Some(L::F),
+        _ => None,
+    }
+}
+
+/// The one-letter code for a type of name component.
+fn name_code(n: N) -> char {
+    match n {
+        N::G => 'G',
+        N::R => 'R',
+        N::N => 'N',
+        N::P => 'P',
+        N::S => 'S',
+    }
+}
+
+/// The type of name component for a one-letter code.
+fn parse_name_code(c: char) -> Option<N> {
+    match c {
+        'G' => Some(N::G),
+        'R' => Some(N::R),
+        'N' => Some(N::N),
+        'P' => Some(N::P),
+        'S' => Some(N::S),
+        _ => None,
+    }
+}
+
+/// Write out a field of a serialized language.
+fn field(out: &mut String, key: &str, vals: &[String]) {
+    out.push_str(key);
+    for v in vals {
+        out.push('\t');
+        out.push_str(v.as_str());
+    }
+    out.push('\n');
+}
+
+/// A letter set as a list of strings.
+fn letters(cs: &Vec<char>) -> Vec<String> {
+    cs.iter().map(|c| c.to_string()).collect()
+}
+
+/// A component of a syllable or surname structure, with a `?` suffix if it's optional.
+fn component(code: char, req: bool) -> String {
+    if req {
+        code.to_string()
+    } else {
+        format!("{}?", code)
+    }
+}
+
+/// The error for a bad value in a serialized language.
+fn bad(key: &str, val: &str) -> String {
+    format!("Bad value {:?} for field {:?}.", val, key)
+}
+
+/// Parse a field which has exactly one value.
+fn parse_one<'a>(key: &str, vals: &[&'a str]) -> Result<&'a str, String> {
+    if vals.len() == 1 {
+        Ok(vals[0])
+    } else {
+        Err(format!("Expected one value for field {:?}.", key))
+    }
+}
+
+/// Parse a single letter.
+fn parse_letter(key: &str, val: &str) -> Result<char, String> {
+    let mut cs = val.chars();
+    match (cs.next(), cs.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(bad(key, val)),
+    }
+}
+
+/// Parse a letter set.
+fn parse_letters(key: &str, vals: &[&str]) -> Result<Vec<char>, String> {
+    vals.iter().map(|v| parse_letter(key, v)).collect()
+}
+
+/// Parse an inclusive range.
+fn parse_range(key: &str, vals: &[&str]) -> Result<(usize, usize), String> {
+    if vals.len() != 2 {
+        return Err(format!("Expected two values for field {:?}.", key));
+    }
+    let lo = try!(vals[0].parse().map_err(|_| bad(key, vals[0])));
+    let hi = try!(vals[1].parse().map_err(|_| bad(key, vals[1])));
+    if lo > hi {
+        return Err(format!("Empty range for field {:?}.", key));
+    }
+    Ok((lo, hi))
+}
+
+/// Parse a syllable or surname structure.
+fn parse_components<T, F>(key: &str, vals: &[&str], code: F) -> Result<Vec<(T, bool)>, String>
+    where F: Fn(char) -> Option<T>
+{
+    vals.iter()
+        .map(|v| {
+            let mut cs = v.chars();
+            let c = try!(cs.next().and_then(|c| code(c)).ok_or_else(|| bad(key, v)));
+            match cs.as_str() {
+                "" => Ok((c, true)),
+                "?" => Ok((c, false)),
+                _ => Err(bad(key, v)),
+            }
+        })
+        .collect()
 }
 
 /// Capitalise the first letter of a string.
@@ -476,3 +925,23 @@ fn capitalise_first(s: String) -> String {
 fn choose<'a, R: Rng, X>(rng: &mut R, xs: &'a Vec<X>) -> &'a X {
     &xs[(rng.next_f64() * xs.len() as f64).floor() as usize]
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, StdRng};
+    use super::Language;
+
+    #[test]
+    fn serialize_round_trip() {
+        let mut rng: StdRng = SeedableRng::from_seed(&[0][..]);
+        let mut lang = Language::new(&mut rng);
+        for _ in 0..10 {
+            let _ = lang.gen_personal(&mut rng);
+            let _ = lang.gen_place(&mut rng);
+        }
+
+        let saved = lang.serialize();
+        let loaded = Language::deserialize(&saved).unwrap();
+        assert_eq!(loaded.serialize(), saved);
+    }
+}
@@ -10,7 +10,9 @@ extern crate sdl2;
 pub mod constants;
 pub mod dijkstra_map;
 pub mod grid;
+pub mod jobs;
 pub mod language;
+pub mod mapgen;
 pub mod mobiles;
 pub mod statics;
 pub mod templates;
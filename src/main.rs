@@ -8,51 +8,340 @@
 extern crate rand;
 extern crate rogue_mayor;
 
-use rand::Rng;
+use rand::{Rng, SeedableRng, StdRng};
+use rogue_mayor::constants::*;
 use rogue_mayor::dijkstra_map::*;
 use rogue_mayor::grid::*;
 use rogue_mayor::language::Language;
+use rogue_mayor::mapgen;
 use rogue_mayor::mobiles::*;
+use rogue_mayor::mobiles::constraints::{Constraints, Filter, MAX_ATTEMPTS};
+use rogue_mayor::mobiles::attributes::ATTRIBUTES;
+use rogue_mayor::mobiles::gen::MobKind;
+use rogue_mayor::mobiles::training::{Stage, Training};
 use rogue_mayor::statics::*;
 use rogue_mayor::templates::*;
 use rogue_mayor::types::*;
 use rogue_mayor::ui::*;
 use rogue_mayor::ui::sdlui::*;
-use std::collections::BTreeMap;
+use rogue_mayor::utils::json_string;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
 
 fn main() {
     if env::args().nth(1) == Some("namegen".to_string()) {
         namegen()
     } else if env::args().nth(1) == Some("chargen".to_string()) {
         chargen()
+    } else if env::args().nth(1) == Some("genstats".to_string()) {
+        genstats()
+    } else if env::args().nth(1) == Some("mapgen".to_string()) {
+        mapgen()
     } else {
         game()
     }
 }
 
-/// Roll a language and print some examples.
+/// Roll a language and print some examples. The type of name ("personal", "place", or "region")
+/// can be given as an argument, and defaults to personal names; or "phonology" prints a summary of
+/// the language instead.
+///
+/// With `--load FILE`, a saved language is used rather than a new one. With `--save FILE`, the
+/// language is saved afterwards, including any morphemes learned while generating the names.
+///
+/// With `--format json`, the names (or the summary) are output as JSON.
 fn namegen() {
     let mut rng = rand::thread_rng();
-    let mut lang = Language::new(&mut rng);
+    let mut mode = "personal".to_string();
+    let mut format = Format::Text;
+    let mut load = None;
+    let mut save = None;
+    let mut args = env::args().skip(2);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--load" => load = Some(args.next().expect("--load needs a file.")),
+            "--save" => save = Some(args.next().expect("--save needs a file.")),
+            "--format" => format = parse_format(&args.next().expect("--format needs a format.")),
+            _ => mode = arg,
+        }
+    }
+
+    let mut lang = match load {
+        Some(path) => load_language(&path),
+        None => Language::new(&mut rng),
+    };
+
+    if format == Format::Csv {
+        panic!("namegen can't output CSV: expected text or json.");
+    }
+
+    if mode == "phonology" {
+        match format {
+            Format::Json => {
+                println!("{{\"type\":\"phonology\",\"summary\":{}}}",
+                         json_string(&lang.describe()))
+            }
+            _ => print!("{}", lang.describe()),
+        }
+    } else {
+        let mut names = Vec::new();
+        for _ in 0..25 {
+            let name = match mode.as_str() {
+                "personal" => lang.gen_personal(&mut rng),
+                "place" => lang.gen_place(&mut rng),
+                "region" => lang.gen_region(&mut rng),
+                _ => {
+                    panic!("Unknown name type {:?}: expected personal, place, region, or \
+                            phonology.",
+                           mode)
+                }
+            };
+            names.push(name);
+        }
+        match format {
+            Format::Json => {
+                let names: Vec<String> = names.iter().map(|n| json_string(n)).collect();
+                println!("{{\"type\":{},\"names\":[{}]}}",
+                         json_string(&mode),
+                         names.join(","))
+            }
+            _ => {
+                for name in names {
+                    println!("{}", name);
+                }
+            }
+        }
+    }
 
-    for _ in 0..25 {
-        let name = lang.gen_personal(&mut rng);
-        println!("{}", name);
+    if let Some(path) = save {
+        let mut file = File::create(&path).expect("Could not create the language file.");
+        file.write_all(lang.serialize().as_bytes()).expect("Could not write the language file.");
+    }
+}
+
+/// Output formats.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    /// Human-readable text.
+    Text,
+    /// JSON.
+    Json,
+    /// CSV, with a header row.
+    Csv,
+}
+
+/// Parse the argument to `--format`.
+fn parse_format(format: &str) -> Format {
+    match format {
+        "text" => Format::Text,
+        "json" => Format::Json,
+        "csv" => Format::Csv,
+        _ => panic!("Unknown format {:?}: expected text, json, or csv.", format),
+    }
+}
+
+/// Load the training packages: from `TRAINING_PATH` if it exists, so that they can be changed
+/// without recompiling, and otherwise the ones built into the game.
+fn load_training() -> Training {
+    let training = if Path::new(TRAINING_PATH).exists() {
+        Training::load(TRAINING_PATH)
+    } else {
+        Training::parse(DEFAULT_TRAINING)
+    };
+    match training {
+        Ok(training) => training,
+        Err(err) => panic!("Could not load the training packages: {}", err),
+    }
+}
+
+/// Load a language saved with `namegen --save`.
+fn load_language(path: &str) -> Language {
+    let mut input = String::new();
+    let _ = File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut input))
+        .expect("Could not read the language file.");
+    match Language::deserialize(&input) {
+        Ok(lang) => lang,
+        Err(err) => panic!("Could not load the language from {:?}: {}", path, err),
     }
 }
 
 /// Roll a character and print their stats.
+///
+/// With `--language FILE`, the character is named in a language saved with `namegen --save`,
+/// rather than a new one.
+///
+/// With `--homeland SEED`, the character is a traveller from the foreign land generated from that
+/// seed, and is named in its language. The same seed always gives the same land.
+///
+/// With `--family`, a whole household is rolled instead.
+///
+/// The character can be constrained, for designing scenarios and testing balance:
+///
+/// - `--type adventurer|child|adult` fixes the kind of character.
+/// - `--age N` fixes their age.
+/// - `--trait NAME` and `--not-trait NAME` fix a personality trait.
+/// - `--package NAME` requires that they have taken a training package or childhood.
+/// - `--until FILTER` requires an attribute to pass a filter, such as `profession_appraiser >= 10`.
+///
+/// Characters are rolled until one satisfies all the constraints. With `--count N`, N characters
+/// (or households) are rolled.
+///
+/// With `--format json` or `--format csv`, the characters are output in full in that format,
+/// rather than as text.
 fn chargen() {
     let mut rng = rand::thread_rng();
-    let mut lang = Language::new(&mut rng);
-    let (ty, mob) = match rng.gen_range(0, 3) {
-        0 => ("Adventurer", Mobile::gen_adventurer(&mut rng, &mut lang)),
-        1 => ("Child", Mobile::gen_child(&mut rng, &mut lang)),
-        _ => ("Ordinary Boring Adult", Mobile::gen_adult(&mut rng, &mut lang)),
+    let training = load_training();
+    let mut language = None;
+    let mut homeland = None;
+    let mut family = false;
+    let mut count = 1;
+    let mut format = Format::Text;
+    let mut constraints = Constraints::default();
+    let mut args = env::args().skip(2);
+    while let Some(arg) = args.next() {
+        let mut value = |what: &str| args.next().expect(&format!("{} needs {}.", arg, what));
+        match arg.as_str() {
+            "--language" => language = Some(load_language(&value("a file"))),
+            "--homeland" => {
+                let seed = value("a numeric seed")
+                    .parse()
+                    .expect("--homeland needs a numeric seed.");
+                let mut lrng: StdRng = SeedableRng::from_seed(&[seed][..]);
+                homeland = Some(Homeland::new(&mut lrng));
+            }
+            "--family" => family = true,
+            "--format" => format = parse_format(&value("a format")),
+            "--count" => count = value("a number").parse().expect("--count needs a number."),
+            "--type" => {
+                constraints.kind = Some(match value("a type").as_str() {
+                    "adventurer" => MobKind::Adventurer,
+                    "child" => MobKind::Child,
+                    "adult" => MobKind::Adult,
+                    ty => panic!("Unknown type {:?}: expected adventurer, child, or adult.", ty),
+                })
+            }
+            "--age" => constraints.age = Some(value("a number").parse().expect("Bad --age.")),
+            "--trait" => constraints.traits.push((value("a trait"), true)),
+            "--not-trait" => constraints.traits.push((value("a trait"), false)),
+            "--package" => constraints.packages.push(value("a training package")),
+            "--until" => {
+                match Filter::parse(&value("a filter")) {
+                    Ok(filter) => constraints.filters.push(filter),
+                    Err(err) => panic!("Bad --until filter: {}", err),
+                }
+            }
+            _ => panic!("Unknown argument {:?}.", arg),
+        }
+    }
+
+    // Travellers are adventurers, unless they've brought their family along.
+    if family && constraints != Constraints::default() {
+        panic!("Households can't be constrained, other than with --count.");
+    }
+    if homeland.is_some() && !family {
+        if constraints.kind.map_or(false, |k| k != MobKind::Adventurer) {
+            panic!("Travellers are always adventurers.");
+        }
+        constraints.kind = Some(MobKind::Adventurer);
+    }
+    if let Err(err) = constraints.check(&training) {
+        panic!("Impossible constraints: {}", err);
+    }
+
+    if format == Format::Csv {
+        println!("{}", Mobile::csv_header());
+    }
+    let mut json = Vec::new();
+    let mut next_id = 0;
+    for n in 0..count {
+        if n > 0 && format == Format::Text {
+            println!("\n");
+        }
+
+        // Travellers share their homeland's language, but everyone else gets the one given, or
+        // a new one.
+        let mut local;
+        let lang = match (homeland.as_mut(), language.as_ref()) {
+            (Some(h), _) => &mut h.lang,
+            (None, Some(l)) => {
+                local = l.clone();
+                &mut local
+            }
+            (None, None) => {
+                local = Language::new(&mut rng);
+                &mut local
+            }
+        };
+
+        let batch = if family {
+            let mobs = Mobile::gen_family(&mut rng, &training, &mut next_id, lang);
+            mobs.into_iter()
+                .enumerate()
+                .map(|(i, mob)| (if i < 2 { "Parent" } else { "Child" }, mob))
+                .collect()
+        } else {
+            let (kind, mut mob) = match Mobile::gen_constrained(&mut rng,
+                                                                &training,
+                                                                &constraints,
+                                                                &mut next_id,
+                                                                lang) {
+                Some(res) => res,
+                None => {
+                    panic!("Gave up after {} characters without satisfying the constraints.",
+                           MAX_ATTEMPTS)
+                }
+            };
+            let ty = match (kind, homeland.as_ref()) {
+                (_, Some(h)) => {
+                    mob.origin = Some(h.name.clone());
+                    "Traveller"
+                }
+                (MobKind::Adventurer, None) => "Adventurer",
+                (MobKind::Child, None) => "Child",
+                (MobKind::Adult, None) => "Ordinary Boring Adult",
+            };
+            vec![(ty, mob)]
+        };
+
+        match format {
+            Format::Text => {
+                let mobs: Vec<Mobile> = batch.iter().map(|&(_, ref mob)| mob.clone()).collect();
+                for &(ty, ref mob) in &batch {
+                    print_mob(&training, mob, ty, &mobs);
+                    if family {
+                        println!("\n");
+                    }
+                }
+            }
+            Format::Json => json.extend(batch.iter().map(|&(ty, ref mob)| mob.to_json(ty))),
+            Format::Csv => {
+                for &(ty, ref mob) in &batch {
+                    println!("{}", mob.to_csv(ty));
+                }
+            }
+        }
+    }
+    if format == Format::Json {
+        println!("[{}]", json.join(",\n "));
+    }
+}
+
+/// Print a character's stats. Their kin are looked up by id in `others`.
+fn print_mob(training: &Training, mob: &Mobile, ty: &str, others: &[Mobile]) {
+    let name_of = |id: usize| {
+        others.iter().find(|m| m.id == id).map_or("someone".to_string(), |m| m.name.clone())
     };
 
-    println!("{} the {} ({} years old)\n", mob.name, ty, mob.age);
+    println!("{} the {} ({} years old)", mob.name, ty, mob.age);
+    match mob.origin {
+        Some(ref origin) => println!("From {}, in {}", mob.birthplace, origin),
+        None => println!("From {}", mob.birthplace),
+    }
+    println!("");
 
     // Personality traits
     let mut f = false;
@@ -80,103 +369,240 @@ fn chargen() {
         println!("");
     }
 
-    // History
+    // Biography
     println!("Biography:");
-    for &(age, event) in &mob.history {
-        match event {
+    let biography = mob.biography(training, |id| {
+        others.iter().find(|m| m.id == id).map(|m| m.name.clone())
+    });
+    for paragraph in biography.split("\n\n") {
+        println!("\t{}\n", paragraph);
+    }
+
+    // History
+    println!("History:");
+    for &(age, ref event) in &mob.history {
+        match *event {
             LifeEvent::Born => println!("\tAge {}: Born.", age),
-            LifeEvent::Raised { childhood } => {
-                println!("\tAge {}: Raised with a {:?} childhood.", age, childhood)
+            LifeEvent::Raised { ref childhood } => {
+                println!("\tAge {}: Raised with a {} childhood.", age, childhood)
             }
-            LifeEvent::Learned { package } => {
-                println!("\tAge {}: Gained experience in {:?}.", age, package)
+            LifeEvent::Learned { ref package } => {
+                println!("\tAge {}: Gained experience in {}.", age, package)
             }
             LifeEvent::Onset => println!("\tAge {}: Became an adventurer.", age),
+            LifeEvent::BornTo { parent } => {
+                println!("\tAge {}: Child of {}.", age, name_of(parent))
+            }
+            LifeEvent::Married { spouse } => {
+                println!("\tAge {}: Married {}.", age, name_of(spouse))
+            }
+            LifeEvent::HadChild { child } => {
+                println!("\tAge {}: Had a child, {}.", age, name_of(child))
+            }
+            LifeEvent::SiblingBorn { sibling } => {
+                println!("\tAge {}: Gained a sibling, {}.", age, name_of(sibling))
+            }
+            LifeEvent::Retired { ref package } => {
+                println!("\tAge {}: Retired from adventuring to work as a {}.", age, package)
+            }
+            LifeEvent::Practised { ref package } => {
+                println!("\tAge {}: Learned {} through practice.", age, package)
+            }
+            LifeEvent::NearDeath => println!("\tAge {}: Nearly died in the dungeon.", age),
+            LifeEvent::GotRich => println!("\tAge {}: Came into money.", age),
+            LifeEvent::LostFriend { friend } => {
+                println!("\tAge {}: Lost a friend, {}.", age, name_of(friend))
+            }
+            LifeEvent::Robbed => println!("\tAge {}: Was robbed.", age),
+            LifeEvent::Changed { ref name, has } => {
+                println!("\tAge {}: {} {}.",
+                         age,
+                         if has { "Became" } else { "Stopped being" },
+                         name)
+            }
         }
     }
 
     // Stats
     println!("\nAttributes:");
-    println!("\tagility: {}", mob.agility);
-    println!("\tconstitution: {}", mob.constitution);
-    println!("\tendurance: {}", mob.endurance);
-    println!("\trecuperation: {}", mob.recuperation);
-    println!("\tstrength: {}", mob.strength);
-    println!("\ttoughness: {}", mob.toughness);
-    println!("\tanimal: {}", mob.animal);
-    println!("\tbargain: {}", mob.bargain);
-    println!("\tcharm: {}", mob.charm);
-    println!("\tchirurgy: {}", mob.chirurgy);
-    println!("\tcraft: {}", mob.craft);
-    println!("\tempathy: {}", mob.empathy);
-    println!("\tforage: {}", mob.forage);
-    println!("\tguile: {}", mob.guile);
-    println!("\theal: {}", mob.heal);
-    println!("\thunt: {}", mob.hunt);
-    println!("\tintuition: {}", mob.intuition);
-    println!("\tlore: {}", mob.lore);
-    if mob.competence_bow > 0 {
-        println!("\tcompetence (bow): {}", mob.competence_bow);
-    }
-    if mob.competence_great > 0 {
-        println!("\tcompetence (great): {}", mob.competence_great);
-    }
-    if mob.competence_shield > 0 {
-        println!("\tcompetence (shield): {}", mob.competence_shield);
-    }
-    if mob.competence_single > 0 {
-        println!("\tcompetence (single): {}", mob.competence_single);
-    }
-    if mob.competence_spear > 0 {
-        println!("\tcompetence (spear): {}", mob.competence_spear);
-    }
-    if mob.competence_staff > 0 {
-        println!("\tcompetence (staff): {}", mob.competence_staff);
-    }
-    if mob.competence_sword > 0 {
-        println!("\tcompetence (sword): {}", mob.competence_sword);
-    }
-    if mob.competence_warhammer > 0 {
-        println!("\tcompetence (warhammer): {}", mob.competence_warhammer);
-    }
-    if mob.profession_adventurer > 0 {
-        println!("\tprofession (adventurer): {}", mob.profession_adventurer);
-    }
-    if mob.profession_animalhandler > 0 {
-        println!("\tprofession (animal handler): {}",
-                 mob.profession_animalhandler);
+    for (attribute, value) in mob.attributes.physical().chain(mob.attributes.mental()) {
+        println!("\t{}: {}", attribute, value);
     }
-    if mob.profession_apothecarist > 0 {
-        println!("\tprofession (apothecarist): {}",
-                 mob.profession_apothecarist);
-    }
-    if mob.profession_appraiser > 0 {
-        println!("\tprofession (appraiser): {}", mob.profession_appraiser);
-    }
-    if mob.profession_cutter > 0 {
-        println!("\tprofession (cutter): {}", mob.profession_cutter);
+    for (attribute, value) in mob.attributes.competences().chain(mob.attributes.professions()) {
+        if value > 0 {
+            println!("\t{}: {}", attribute, value);
+        }
     }
-    if mob.profession_farmer > 0 {
-        println!("\tprofession (farmer): {}", mob.profession_farmer);
+}
+
+/// Roll many characters of each kind (1000, unless a number is given as an argument) and print
+/// statistics about them: the distributions of age and each attribute, how common each personality
+/// trait is, and how often each training package is taken in each stage of life. This is for
+/// checking that changes to the generator or the training packages keep populations believable.
+fn genstats() {
+    let count: usize = match env::args().nth(2) {
+        Some(n) => n.parse().expect("The number of characters must be a number."),
+        None => 1000,
+    };
+    let mut rng = rand::thread_rng();
+    let training = load_training();
+    let mut lang = Language::new(&mut rng);
+    let mut next_id = 0;
+
+    for &(kind, ty) in &[(MobKind::Adventurer, "Adventurers"),
+                         (MobKind::Child, "Children"),
+                         (MobKind::Adult, "Adults")] {
+        let mobs: Vec<Mobile> = (0..count)
+            .map(|_| {
+                Mobile::gen_kind(&mut rng, &training, kind, None, &[], &mut next_id, &mut lang)
+            })
+            .collect();
+
+        println!("{} ({}):\n", ty, count);
+        println!("\t{:<28} {:>5} {:>5} {:>6} {:>7} {:>5} {:>5}",
+                 "",
+                 "min",
+                 "p10",
+                 "median",
+                 "mean",
+                 "p90",
+                 "max");
+        print_distribution("age", mobs.iter().map(|m| m.age).collect());
+        for attribute in ATTRIBUTES.iter() {
+            let values: Vec<usize> = mobs.iter().map(|m| m.attributes.get(*attribute)).collect();
+            if values.iter().any(|&v| v > 0) {
+                print_distribution(&attribute.to_string(), values);
+            }
+        }
+
+        println!("\n\tPersonality:");
+        for name in PERSONALITY_TRAITS.iter() {
+            let n = mobs.iter().filter(|m| m.personality_trait(name) == Some(true)).count();
+            println!("\t\t{:<26} {:>5.1}%", name, percent(n, count));
+        }
+
+        // Count the mobs taking each package at least once in each stage.
+        let mut taken: BTreeMap<(Stage, String), usize> = BTreeMap::new();
+        for mob in &mobs {
+            let mut seen = BTreeSet::new();
+            for &(age, ref event) in &mob.history {
+                let (stage, name) = match *event {
+                    LifeEvent::Raised { ref childhood } => (Stage::Childhood, childhood),
+                    LifeEvent::Learned { ref package } => {
+                        let years = training.get(package).map_or(0, |p| p.years);
+                        (mob.stage_at(age - years), package)
+                    }
+                    _ => continue,
+                };
+                if seen.insert((stage, name.clone())) {
+                    *taken.entry((stage, name.clone())).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut last = None;
+        for (&(stage, ref name), &n) in &taken {
+            if last != Some(stage) {
+                println!("\n\t{:?} packages:", stage);
+                last = Some(stage);
+            }
+            println!("\t\t{:<26} {:>5.1}%", name, percent(n, count));
+        }
+
+        if kind == MobKind::Adventurer {
+            let fresh = mobs.iter().filter(|m| m.onset_age.map_or(true, |o| o == m.age)).count();
+            println!("\n\tWith no post-onset years: {:.1}%", percent(fresh, count));
+        }
+        println!("\n");
     }
-    if mob.profession_innkeeper > 0 {
-        println!("\tprofession (innkeeper): {}", mob.profession_innkeeper);
+}
+
+/// Print a summary of the distribution of some values, for `genstats`.
+fn print_distribution(label: &str, mut values: Vec<usize>) {
+    if values.is_empty() {
+        return;
     }
-    if mob.profession_laborer > 0 {
-        println!("\tprofession (laborer): {}", mob.profession_laborer);
+    values.sort();
+    let percentile = |p: usize| values[(values.len() - 1) * p / 100];
+    let mean = values.iter().sum::<usize>() as f64 / values.len() as f64;
+    println!("\t{:<28} {:>5} {:>5} {:>6} {:>7.1} {:>5} {:>5}",
+             label,
+             values[0],
+             percentile(10),
+             percentile(50),
+             mean,
+             percentile(90),
+             values[values.len() - 1]);
+}
+
+/// `n` as a percentage of `total`.
+fn percent(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * n as f64 / total as f64
     }
-    if mob.profession_tinker > 0 {
-        println!("\tprofession (tinker): {}", mob.profession_tinker);
+}
+
+/// Generate a starting map and print an ASCII preview of it. The seed can be given as an argument.
+fn mapgen() {
+    let seed = match env::args().nth(2) {
+        Some(s) => s.parse().expect("The seed must be a number."),
+        None => rand::thread_rng().gen(),
+    };
+    let mut rng: StdRng = SeedableRng::from_seed(&[seed][..]);
+    let mut maps = Maps::new();
+    let lang = Language::new(&mut rng);
+    let mut world = World::new(lang, load_training());
+    let site = mapgen::generate(&mut rng, &mut world, &mut maps);
+
+    println!("Seed: {}\n", seed);
+    for y in 0..HEIGHT {
+        let mut row = String::with_capacity(WIDTH);
+        for x in 0..WIDTH {
+            let p = Point { x: x, y: y };
+            row.push(if p == site.town {
+                '@'
+            } else {
+                match world.statics.at(p).map(|s| s.tag) {
+                    Some(StaticTag::Dungeon) => 'D',
+                    Some(StaticTag::Road) => '#',
+                    Some(StaticTag::Tree) => 'T',
+                    Some(StaticTag::Rock) => '^',
+                    Some(StaticTag::ShallowWater) => '~',
+                    Some(StaticTag::DeepWater) => '=',
+                    Some(StaticTag::Grass) | None => '.',
+                    Some(_) => '?',
+                }
+            });
+        }
+        println!("{}", row);
     }
-    if mob.profession_trader > 0 {
-        println!("\tprofession (trader): {}", mob.profession_trader);
+    println!("\n@ town  D dungeon  # road  T tree  ^ rock  ~ shallow water  = deep water");
+
+    // The river is named in stretches, so only print each name once.
+    println!("\nPlaces:");
+    let mut seen = Vec::new();
+    for place in &world.places {
+        if !seen.contains(&place.name) {
+            println!("\t{} ({:?}) at {},{}",
+                     place.name,
+                     place.kind,
+                     (place.top_left.x + place.bottom_right.x) / 2,
+                     (place.top_left.y + place.bottom_right.y) / 2);
+            seen.push(place.name.clone());
+        }
     }
-    if mob.profession_woodsman > 0 {
-        println!("\tprofession (woodsman): {}", mob.profession_woodsman);
+
+    println!("\nForeign lands:");
+    for homeland in &mut world.foreign {
+        println!("\t{} (e.g. {})",
+                 homeland.name,
+                 homeland.lang.gen_personal(&mut rng));
     }
 }
 
-/// Play the game!
+/// Play the game! With `--language FILE`, the town speaks a language saved with `namegen --save`
+/// rather than a new one.
 fn game() {
     println!("Welcome to Rogue Mayor!");
 
@@ -185,27 +611,48 @@ fn game() {
             // Set up the state.
             let mut maps: Maps = Maps::new();
             let mut mobs: BTreeMap<Point, Mobile> = BTreeMap::new();
-            let mut world: World = World::new();
-            world.cursor = SdlUI::initial_cursor();
 
-            // Everyone likes welcomes.
+            // Generate the map, and start with the town in view.
+            let seed = rand::thread_rng().gen();
+            let mut rng: StdRng = SeedableRng::from_seed(&[seed][..]);
+            let lang = match (env::args().nth(1), env::args().nth(2)) {
+                (Some(ref arg), Some(ref path)) if arg == "--language" => load_language(path),
+                _ => Language::new(&mut rng),
+            };
+            let mut world: World = World::new(lang, load_training());
+            let site = mapgen::generate(&mut rng, &mut world, &mut maps);
+            world.cursor = site.town;
+
+            // Settle some townsfolk, and give them jobs.
+            for _ in 0..STARTING_HOUSEHOLDS {
+                let _ = world.settle_household(&mut rng, &mut mobs, site.town);
+            }
+            world.assign_jobs(&mut maps, &mut mobs);
+
             world.log(Message {
-                msg: "Welcome to Rogue Mayor!".to_string(),
+                msg: format!("The map was generated from seed {}.", seed),
                 loc: None,
             });
 
-            // Testing stuff
-            let pos = Point { x: 25, y: 25 };
-            world.statics.set(pos, Some(Static::new(StaticTag::Dungeon)));
-            maps.mutget(MapTag::Adventure).add_source(pos, &world);
-            let _ = world.sources.insert(pos, MapTag::Adventure);
+            // Everyone likes welcomes.
+            world.log(Message {
+                msg: format!("Welcome to {}, Mayor!", world.name),
+                loc: Some(site.town),
+            });
 
             // Game loop
             'game: loop {
                 // Update all mobs: clone the mob map, as we're going to be mutating it then, for
                 // each mob in the original map, check if it's still in the old map (it might have
                 // been killed) and step it. This may also mutate the maps, if a mob performs a
-                // map-relevant action.
+                // map-relevant action. Anything mobs do to each other happens once they've all had
+                // their turn. At the start of each day, the day's taxes are collected, a traveller
+                // arrives along the road, and jobs are handed out.
+                if world.is_new_day() {
+                    world.collect_taxes();
+                    let _ = world.welcome_traveller(&mut rng, &mut mobs, site.arrival);
+                    world.assign_jobs(&mut maps, &mut mobs);
+                }
                 let mut new_mobs = mobs.clone();
                 for (pos, mob) in mobs.iter_mut() {
                     // This check is perhaps too lenient. For example, if Mob A destroys Mob B and
@@ -213,10 +660,11 @@ fn game() {
                     // is new. This can be explained away by saying that Mob B wasn't destroyed,
                     // merely transformed into Mob C...
                     if new_mobs.contains_key(pos) {
-                        mob.step(pos.clone(), &mut new_mobs, &mut maps, &mut world);
+                        mob.step(&mut rng, pos.clone(), &mut new_mobs, &mut maps, &mut world);
                     }
                 }
                 mobs = new_mobs;
+                world.apply_effects(&mut rng, &mut mobs);
 
                 let mut has_advanced = true;
                 'ui: loop {
@@ -229,14 +677,35 @@ fn game() {
                     let action = ui.input(world.cursor);
                     match action {
                         Command::BuildTemplate => {
-                            world.build(&mut maps);
-                            world.template = None;
+                            if world.build(&mut rng, &mut maps) {
+                                world.template = None;
+                                world.assign_jobs(&mut maps, &mut mobs);
+                            }
+                        }
+                        Command::Paint(tag, shape, from, to) => {
+                            let _ = world.paint(&mut maps, tag, shape, from, to);
                         }
                         Command::Quit => break 'game,
+                        Command::Redo => {
+                            if world.redo(&mut maps) {
+                                world.assign_jobs(&mut maps, &mut mobs);
+                            }
+                        }
                         Command::Render => {}
                         Command::SetCursorTo(c) => world.cursor = c,
                         Command::SetTemplateTo(t) => world.template = Some(Template::new(t)),
                         Command::Step => break 'ui,
+                        Command::ToggleDoor(p) => {
+                            let _ = world.toggle_door(&mut maps, p);
+                        }
+                        Command::ToggleLock(p) => {
+                            let _ = world.toggle_lock(&mut maps, p);
+                        }
+                        Command::Undo => {
+                            if world.undo(&mut maps) {
+                                world.assign_jobs(&mut maps, &mut mobs);
+                            }
+                        }
                     }
 
                     // Testing the message log.
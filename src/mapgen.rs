@@ -0,0 +1,316 @@
+//! Starting map generation. The map is built up in layers: first everything is grass, then hills
+//! and forests are scattered about, and a river is cut through the middle. Then a site for the town
+//! is picked in the most open area, the dungeon entrance is placed a fair walk away (preferably in
+//! the hills), and a road is laid from the town to the nearest edge of the map, which is where
+//! travellers will arrive. The town, the dungeon, and the hills, forests, and river around them are
+//! all given names in the world's language. Finally, a few foreign lands beyond the edge of the map
+//! are made up, each with its own language, for travellers to come from.
+
+use constants::*;
+use dijkstra_map::*;
+use grid::*;
+use rand::Rng;
+use statics::*;
+use std::cmp;
+use templates::*;
+use types::*;
+use utils::*;
+
+/// The number of hills to scatter about.
+const HILLS: usize = 6;
+
+/// The number of forests to scatter about.
+const FORESTS: usize = 8;
+
+/// The number of foreign lands travellers come from.
+const FOREIGN_LANDS: usize = 3;
+
+/// The minimum distance from the town to the dungeon.
+const MIN_DUNGEON_DIST: usize = 40;
+
+/// The maximum distance from the town to the dungeon.
+const MAX_DUNGEON_DIST: usize = 70;
+
+/// The radius of the area cleared for the town.
+const TOWN_RADIUS: usize = 12;
+
+/// The number of cells from the edge of the map the town can't be within.
+const TOWN_MARGIN: usize = 20;
+
+/// The number of candidate points considered when placing the town or the dungeon.
+const CANDIDATES: usize = 250;
+
+/// The interesting locations of a generated map.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Site {
+    /// The centre of the town site.
+    pub town: Point,
+    /// The dungeon entrance.
+    pub dungeon: Point,
+    /// Where the road meets the edge of the map.
+    pub arrival: Point,
+}
+
+/// Generate the starting map into an empty world, and rebuild the heatmaps.
+pub fn generate<R: Rng>(rng: &mut R, world: &mut World, maps: &mut Maps) -> Site {
+    // Terrain.
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            world.statics.set(Point { x: x, y: y }, Some(Static::new(StaticTag::Grass)));
+        }
+    }
+    for _ in 0..HILLS {
+        let centre = random_point(rng, 0);
+        let radius = rng.gen_range(4, 10);
+        blob(rng, world, centre, radius, StaticTag::Rock, 0.9);
+        let name = format!("The {} Hills", world.lang.gen_region(rng));
+        name_area(world, name, PlaceKind::Region, centre, radius);
+    }
+    for _ in 0..FORESTS {
+        let centre = random_point(rng, 0);
+        let radius = rng.gen_range(8, 20);
+        blob(rng, world, centre, radius, StaticTag::Tree, 0.6);
+        let name = if rng.gen() {
+            format!("{} Forest", world.lang.gen_region(rng))
+        } else {
+            format!("{} Wood", world.lang.gen_region(rng))
+        };
+        name_area(world, name, PlaceKind::Region, centre, radius);
+    }
+    river(rng, world);
+
+    // The town goes in the most open area, which is then cleared.
+    let town = best_of(rng, TOWN_MARGIN, |p| open_area(world, p), |_| true);
+    clear(world, town, TOWN_RADIUS);
+    world.name = world.lang.gen_place(rng);
+    let name = world.name.clone();
+    name_area(world, name, PlaceKind::Town, town, TOWN_RADIUS);
+
+    // The dungeon goes a fair walk from the town, preferably in the hills. There needs to be a way
+    // to get there.
+    let dungeon = best_of(rng,
+                          1,
+                          |p| rocks_nearby(world, p),
+                          |p| {
+                              let d = distance(town, p);
+                              d >= MIN_DUNGEON_DIST && d <= MAX_DUNGEON_DIST
+                          });
+    clear(world, dungeon, 1);
+    for p in line(town, dungeon) {
+        clear(world, p, 0);
+    }
+
+    // Then a road out of town.
+    let arrival = road(rng, world, town);
+
+    // Finally, the dungeon entrance itself. This goes through `World::place` so that the sources
+    // are set up and the heatmaps rebuilt.
+    let (tpl, pos) = Template::paint(StaticTag::Dungeon, Shape::Line, dungeon, dungeon);
+    let _ = world.place(maps, &tpl, pos);
+    let name = format!("The Depths of {}", world.lang.gen_place(rng));
+    name_area(world, name, PlaceKind::Dungeon, dungeon, 0);
+
+    // And the lands beyond.
+    for _ in 0..FOREIGN_LANDS {
+        world.foreign.push(Homeland::new(rng));
+    }
+
+    Site {
+        town: town,
+        dungeon: dungeon,
+        arrival: arrival,
+    }
+}
+
+/// Pick a random point, at least `margin` cells from the edge of the map.
+fn random_point<R: Rng>(rng: &mut R, margin: usize) -> Point {
+    Point {
+        x: rng.gen_range(margin, WIDTH - margin),
+        y: rng.gen_range(margin, HEIGHT - margin),
+    }
+}
+
+/// Pick the highest-scoring of a number of random points satisfying a predicate. If no candidate
+/// satisfies the predicate, the highest-scoring of them all is used.
+fn best_of<R: Rng, F, P>(rng: &mut R, margin: usize, score: F, pred: P) -> Point
+    where F: Fn(Point) -> usize,
+          P: Fn(Point) -> bool
+{
+    let mut best = None;
+    let mut fallback = None;
+    for _ in 0..CANDIDATES {
+        let p = random_point(rng, margin);
+        let s = score(p);
+        if pred(p) && best.map_or(true, |(_, bs)| s > bs) {
+            best = Some((p, s));
+        }
+        if fallback.map_or(true, |(_, fs)| s > fs) {
+            fallback = Some((p, s));
+        }
+    }
+
+    best.or(fallback).unwrap().0
+}
+
+/// Scatter a static around a point, more densely towards the centre.
+fn blob<R: Rng>(rng: &mut R,
+                world: &mut World,
+                centre: Point,
+                radius: usize,
+                tag: StaticTag,
+                density: f64) {
+    for p in around(centre, radius) {
+        let d = distance(centre, p);
+        if d <= radius && rng.next_f64() < density * (1.0 - d as f64 / (radius + 1) as f64) {
+            world.statics.set(p, Some(Static::new(tag)));
+        }
+    }
+}
+
+/// Name the square of the given radius around a point.
+fn name_area(world: &mut World, name: String, kind: PlaceKind, centre: Point, radius: usize) {
+    world.places.push(Place {
+        name: name,
+        kind: kind,
+        top_left: Point {
+            x: centre.x.saturating_sub(radius),
+            y: centre.y.saturating_sub(radius),
+        },
+        bottom_right: Point {
+            x: cmp::min(WIDTH - 1, centre.x + radius),
+            y: cmp::min(HEIGHT - 1, centre.y + radius),
+        },
+    });
+}
+
+/// Cut a meandering river from the top of the map to the bottom. It's deep in the middle, with
+/// shallow banks, and has a ford somewhere along it.
+///
+/// As a river is long and thin, it is named in stretches, each with the same name.
+fn river<R: Rng>(rng: &mut R, world: &mut World) {
+    let name = format!("The {} River", world.lang.gen_region(rng));
+    let ford = rng.gen_range(HEIGHT / 4, 3 * HEIGHT / 4);
+    let mut x = rng.gen_range(WIDTH / 4, 3 * WIDTH / 4);
+    let mut drift: i8 = 0;
+    for y in 0..HEIGHT {
+        if y % 5 == 0 {
+            name_area(world,
+                      name.clone(),
+                      PlaceKind::Region,
+                      Point { x: x + 1, y: y + 2 },
+                      4);
+        }
+
+        // The river tends to keep going in the same direction for a while.
+        if rng.gen_range(0, 5) == 0 {
+            drift = *rng.choose(&[-1, 0, 1]).unwrap();
+        }
+        x = cmp::max(2, cmp::min(WIDTH - 4, signed_add(x, drift)));
+
+        for dx in 0..4 {
+            let deep = dx == 1 || dx == 2;
+            let tag = if deep && (y < ford || y > ford + 2) {
+                StaticTag::DeepWater
+            } else {
+                StaticTag::ShallowWater
+            };
+            world.statics.set(Point { x: x + dx - 1, y: y }, Some(Static::new(tag)));
+        }
+    }
+}
+
+/// Lay a road from the town to the nearest edge of the map. Returns where it meets the edge.
+fn road<R: Rng>(rng: &mut R, world: &mut World, town: Point) -> Point {
+    // Work out which edge is closest, and which way to go to get there.
+    let (dx, dy): (i8, i8) = {
+        let to_left = town.x;
+        let to_right = WIDTH - 1 - town.x;
+        let to_top = town.y;
+        let to_bottom = HEIGHT - 1 - town.y;
+        let nearest = cmp::min(cmp::min(to_left, to_right), cmp::min(to_top, to_bottom));
+        if nearest == to_left {
+            (-1, 0)
+        } else if nearest == to_right {
+            (1, 0)
+        } else if nearest == to_top {
+            (0, -1)
+        } else {
+            (0, 1)
+        }
+    };
+
+    let mut pos = town;
+    loop {
+        world.statics.set(pos, Some(Static::new(StaticTag::Road)));
+
+        if (dx < 0 && pos.x == 0) || (dx > 0 && pos.x == WIDTH - 1) ||
+           (dy < 0 && pos.y == 0) || (dy > 0 && pos.y == HEIGHT - 1) {
+            return pos;
+        }
+
+        // Mostly head straight for the edge, but wander a little to the side now and then.
+        if rng.gen_range(0, 4) == 0 {
+            let side = *rng.choose(&[-1, 1]).unwrap();
+            if dx == 0 {
+                pos.x = cmp::max(1, cmp::min(WIDTH - 2, signed_add(pos.x, side)));
+            } else {
+                pos.y = cmp::max(1, cmp::min(HEIGHT - 2, signed_add(pos.y, side)));
+            }
+            world.statics.set(pos, Some(Static::new(StaticTag::Road)));
+        }
+        pos.x = signed_add(pos.x, dx);
+        pos.y = signed_add(pos.y, dy);
+    }
+}
+
+/// Clear the trees and rocks around a point, and make any deep water shallow enough to wade
+/// through.
+fn clear(world: &mut World, centre: Point, radius: usize) {
+    for p in around(centre, radius) {
+        if distance(centre, p) > radius {
+            continue;
+        }
+        match world.statics.at(p).map(|s| s.tag) {
+            Some(StaticTag::Tree) |
+            Some(StaticTag::Rock) => world.statics.set(p, Some(Static::new(StaticTag::Grass))),
+            Some(StaticTag::DeepWater) => {
+                world.statics.set(p, Some(Static::new(StaticTag::ShallowWater)))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The number of grass cells around a point.
+fn open_area(world: &World, centre: Point) -> usize {
+    around(centre, TOWN_RADIUS)
+        .into_iter()
+        .filter(|p| world.statics.at(*p).map_or(false, |s| s.tag == StaticTag::Grass))
+        .count()
+}
+
+/// The number of rock cells near a point.
+fn rocks_nearby(world: &World, centre: Point) -> usize {
+    around(centre, 5)
+        .into_iter()
+        .filter(|p| world.statics.at(*p).map_or(false, |s| s.tag == StaticTag::Rock))
+        .count()
+}
+
+/// All the points in the square of the given radius around a point, clipped to the map.
+fn around(centre: Point, radius: usize) -> Vec<Point> {
+    let mut points = Vec::new();
+    for y in centre.y.saturating_sub(radius)..cmp::min(HEIGHT, centre.y + radius + 1) {
+        for x in centre.x.saturating_sub(radius)..cmp::min(WIDTH, centre.x + radius + 1) {
+            points.push(Point { x: x, y: y });
+        }
+    }
+    points
+}
+
+/// The (rounded) straight-line distance between two points.
+fn distance(a: Point, b: Point) -> usize {
+    let dx = a.x as f64 - b.x as f64;
+    let dy = a.y as f64 - b.y as f64;
+    (dx * dx + dy * dy).sqrt().round() as usize
+}
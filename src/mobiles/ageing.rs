@@ -0,0 +1,113 @@
+//! Ageing, once the game is underway. On their birthday a mob gets a year older, and that year may
+//! bring some changes: the old slowly lose their physical edge, adventurers eventually hang up their
+//! swords and take up a quieter profession, and everyone dies in the end.
+
+use dijkstra_map::MapTag;
+use grid::Point;
+use mobiles::{LifeEvent, Mobile};
+use mobiles::attributes::{Attribute, Category};
+use mobiles::schedule::Schedule;
+use mobiles::training::{Stage, TrainingPackage};
+use rand::Rng;
+use types::*;
+
+/// The age at which a mob starts to decline, and becomes at risk of dying of old age.
+pub const OLD_AGE: usize = 50;
+
+/// The age at which adventurers start to think about retiring.
+pub const RETIREMENT_AGE: usize = 35;
+
+/// How much each physical attribute falls by in a year of old age.
+const DECLINE_PER_YEAR: usize = 2;
+
+/// The chance, per year past `RETIREMENT_AGE`, of an adventurer retiring.
+const RETIREMENT_CHANCE_PER_YEAR: f64 = 0.05;
+
+/// The chance, per year past `OLD_AGE`, of dying of old age.
+const DEATH_CHANCE_PER_YEAR: f64 = 0.02;
+
+impl Mobile {
+    /// The mob has had a birthday. Age them a year, and see whether they decline, retire, or die.
+    /// Returns `false` if they have died, in which case the caller should remove them.
+    pub fn have_birthday<R: Rng>(&mut self, rng: &mut R, pos: Point, world: &mut World) -> bool {
+        self.age += 1;
+
+        if self.age > OLD_AGE {
+            let years = (self.age - OLD_AGE) as f64;
+            if rng.next_f64() < years * DEATH_CHANCE_PER_YEAR {
+                world.log(Message {
+                    msg: format!("{} has died of old age, at {}.", self.name, self.age),
+                    loc: Some(pos),
+                });
+                return false;
+            }
+
+            let physical: Vec<Attribute> = self.attributes.physical().map(|(a, _)| a).collect();
+            for attribute in physical {
+                self.attributes.sub(attribute, DECLINE_PER_YEAR);
+            }
+        }
+
+        // The brave keep going for longer; but the old, or those who've lost their nerve, will
+        // retire sooner or later.
+        if self.onset_age.is_some() && self.retirement_age.is_none() {
+            let years = self.age.saturating_sub(RETIREMENT_AGE) as f64;
+            let chance = if self.is_brave {
+                years * RETIREMENT_CHANCE_PER_YEAR
+            } else {
+                2.0 * years * RETIREMENT_CHANCE_PER_YEAR + 0.1
+            };
+            if rng.next_f64() < chance {
+                self.retire(rng, pos, world);
+            }
+        }
+
+        // Growing up or retiring changes the daily routine.
+        self.schedule = Schedule::for_mob(self);
+
+        true
+    }
+
+    /// Give up adventuring, and take up a profession: one they have trained in if possible, and
+    /// otherwise any which an adventurer could take up.
+    pub fn retire<R: Rng>(&mut self, rng: &mut R, pos: Point, world: &mut World) {
+        let package = {
+            let candidates: Vec<&TrainingPackage> = world.training
+                .stage(Stage::PostOnset)
+                .into_iter()
+                .filter(|p| is_civilian_profession(p))
+                .collect();
+            let learned: Vec<&TrainingPackage> =
+                candidates.iter().cloned().filter(|p| self.has_learned(&p.name)).collect();
+            if learned.is_empty() {
+                rng.choose(&candidates).map(|p| p.name.clone())
+            } else {
+                rng.choose(&learned).map(|p| p.name.clone())
+            }
+        };
+
+        let age = self.age;
+        self.retirement_age = Some(age);
+        let _ = self.desires.remove(&MapTag::Adventure);
+        let msg = match package {
+            Some(ref name) => {
+                format!("{} has retired from adventuring, to work as a {}.", self.name, name)
+            }
+            None => format!("{} has retired from adventuring.", self.name),
+        };
+        if let Some(name) = package {
+            self.add_history(age, LifeEvent::Retired { package: name });
+        }
+        world.log(Message {
+            msg: msg,
+            loc: Some(pos),
+        });
+    }
+}
+
+/// Whether a training package grants a profession other than adventuring.
+fn is_civilian_profession(package: &TrainingPackage) -> bool {
+    package.grants.iter().any(|&(a, _)| {
+        a.category() == Category::Profession && a != Attribute::ProfessionAdventurer
+    })
+}
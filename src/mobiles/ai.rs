@@ -3,7 +3,10 @@
 use constants::{HEIGHT, WIDTH};
 use dijkstra_map::{Map, MapTag, Maps};
 use grid::Point;
-use mobiles::Mobile;
+use mobiles::{Effect, Mobile};
+use mobiles::attributes::Attribute;
+use mobiles::behaviour::{Blackboard, Context};
+use rand::Rng;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::f64;
@@ -14,69 +17,39 @@ use utils::*;
 const SIGHT_BONUS: f64 = 100.0;
 
 impl Mobile {
-    /// The AI. This uses a behaviour tree-style approach to decision making, see the in-code
-    /// comments.
-    pub fn ai(&mut self,
-              pos: Point,
-              mobs: &mut BTreeMap<Point, Mobile>,
-              maps: &mut Maps,
-              world: &mut World) {
-        // 1. INTERACT AT POINT
-        //
-        // Can the mob interact with something that it wants to interact with?
-        if let Some(target_pos) = self.ai_interact_nearby(pos, maps) {
-            if self.ai_interact_at_point_commit(world, target_pos) {
-                return;
-            }
-        }
-
-        // 2. PRIORITY TASK
-        //
-        // Does the mob have something that it absolutely must do, as a matter of utmost priority?
-        // Furthermore, can it make useful progress towards completing that task? If so, do that!
-        if let Some(ref task) = self.priority_task {
-            if let Some(new_pos) = self.ai_advance_task(pos, world, task) {
-                if self.ai_move_commit(pos, mobs, world, new_pos) {
-                    return;
-                }
-            }
-        }
-
-        // 3. HEATMAP TRAVEL
-        //
-        // Take the desire-weighted sum of the heatmaps, and determine if there's a place we'd like
-        // to move towards.
-        //
-        // To encourage a "sensible" pathing behaviour, check if there are any desires we can see
-        // the source of from this point. If so, we *particularly* want to move towards it (or away
-        // from it, if it's scary), even if there are other more pressing desires. This discourages
-        // back-and-forth travel.
-        if let Some(new_pos) = self.ai_heatmap_wsum(pos, maps, world) {
-            if self.ai_move_commit(pos, mobs, world, new_pos) {
-                return;
-            }
-        }
-
-        // 4. WANDERING
-        //
-        // The mob has reached a state of zen. It has nothing left to do in this mortal
-        // plane. Satori is within reach. Just wander home.
-        if let Some(new_pos) = self.ai_pathfind(pos, world, self.home_pos) {
-            if self.ai_move_commit(pos, mobs, world, new_pos) {
-                return;
-            }
-        }
-
-        // 5. GIVING UP
-        //
-        // Just stay where we are.
+    /// The AI. This runs the behaviour tree for the mob's archetype (see the `behaviour` module),
+    /// with the functions below as its leaves. For most mobs, that means:
+    ///
+    /// 1. Interact with something nearby which satisfies a desire.
+    /// 2. Make progress on the priority task, if there is one.
+    /// 3. Follow the desire-weighted sum of the heatmaps. To encourage a "sensible" pathing
+    ///    behaviour, sources which can be seen are *particularly* attractive (or scary), even if
+    ///    there are other more pressing desires. This discourages back-and-forth travel.
+    /// 4. The mob has reached a state of zen. It has nothing left to do in this mortal plane.
+    ///    Satori is within reach. Just wander home.
+    /// 5. Give up, and stay where we are.
+    pub fn ai<R: Rng>(&mut self,
+                      rng: &mut R,
+                      pos: Point,
+                      mobs: &mut BTreeMap<Point, Mobile>,
+                      maps: &mut Maps,
+                      world: &mut World) {
+        let tree = self.archetype().tree();
+        let mut blackboard = Blackboard::default();
+        let mut ctx = Context {
+            pos: pos,
+            mobs: mobs,
+            maps: maps,
+            world: world,
+        };
+        let _ = self.run_tree(rng, &tree, &mut blackboard, &mut ctx);
     }
 
     // The `ai_*` functions do not modify the state of the game, and return an `Option` indicating
     // success (with further information) or failure.
 
     /// Determine if there is a nearby point we can interact with to satisfy a desire.
-    fn ai_interact_nearby(&self, pos: Point, maps: &Maps) -> Option<Point> {
+    pub fn ai_interact_nearby(&self, pos: Point, maps: &Maps) -> Option<Point> {
         // At the moment, just see if any of the >0.0-weighted heatmaps have an adjacent source.
         for dy in inclusive_range(-1, 1) {
             if (dy < 0 && pos.y == 0) || (dy > 0 && pos.y == HEIGHT - 1) {
@@ -102,24 +75,18 @@ impl Mobile {
         None
     }
 
-    /// Determine a position to move to which will advance the given task.
-    fn ai_advance_task(&self, pos: Point, world: &World, target: &Task) -> Option<Point> {
-        match target {
-            &Task::MoveTo(target_point) => self.ai_pathfind(pos, world, target_point),
-        }
-    }
-
     /// Choose a point to move to by taking the desire-weighted sum of the heatmaps. If a weight is
     /// negative, flee. If the source is visible, intensify the weight.
     ///
     /// See:
     /// - http://www.roguebasin.com/index.php?title=The_Incredible_Power_of_Dijkstra_Maps
     /// - http://www.roguebasin.com/index.php?title=Dijkstra_Maps_Visualized
-    fn ai_heatmap_wsum(&self, pos: Point, maps: &Maps, world: &World) -> Option<Point> {
-        // Work out what sources are visible from here.
+    pub fn ai_heatmap_wsum(&self, pos: Point, maps: &Maps, world: &World) -> Option<Point> {
+        // Work out what sources are visible from here. Those which aren't on their heatmap (such
+        // as an inn with nobody behind the counter) don't count.
         let mut locally_visible = BTreeSet::new();
         for (p, tag) in &world.sources {
-            if can_see(pos, *p, world) {
+            if maps.get(*tag).sources.contains(p) && can_see(pos, *p, world) {
                 locally_visible.insert(tag);
             }
         }
@@ -174,24 +141,75 @@ impl Mobile {
         new_pos
     }
 
+    /// Find the next step towards the nearest source of a heatmap.
+    pub fn ai_approach(&self, pos: Point, maps: &Maps, tag: MapTag) -> Option<Point> {
+        maps.get(tag).get_new_pos(pos).and_then(|p| if p == pos { None } else { Some(p) })
+    }
+
+    /// Find the adjacent point furthest from a source of danger.
+    pub fn ai_flee(&self, pos: Point, world: &World, from: Point) -> Option<Point> {
+        let distance = |p: Point| {
+            let dx = p.x as f64 - from.x as f64;
+            let dy = p.y as f64 - from.y as f64;
+            dx * dx + dy * dy
+        };
+        let mut best = None;
+        let mut best_distance = distance(pos);
+        for dy in inclusive_range(-1, 1) {
+            if (dy < 0 && pos.y == 0) || (dy > 0 && pos.y == HEIGHT - 1) {
+                continue;
+            }
+            for dx in inclusive_range(-1, 1) {
+                if (dx < 0 && pos.x == 0) || (dx > 0 && pos.x == WIDTH - 1) {
+                    continue;
+                }
+                let p = Point {
+                    x: signed_add(pos.x, dx),
+                    y: signed_add(pos.y, dy),
+                };
+                if distance(p) > best_distance &&
+                   !world.statics.at(p).map_or(false, |s| s.is_impassable) {
+                    best = Some(p);
+                    best_distance = distance(p);
+                }
+            }
+        }
+        best
+    }
+
+    /// Pick a random adjacent point, which might not be free.
+    pub fn ai_wander<R: Rng>(&self, rng: &mut R, pos: Point) -> Option<Point> {
+        let dx = rng.gen_range(-1, 2);
+        let dy = rng.gen_range(-1, 2);
+        if (dx == 0 && dy == 0) || (dx < 0 && pos.x == 0) || (dx > 0 && pos.x == WIDTH - 1) ||
+           (dy < 0 && pos.y == 0) || (dy > 0 && pos.y == HEIGHT - 1) {
+            None
+        } else {
+            Some(Point {
+                x: signed_add(pos.x, dx),
+                y: signed_add(pos.y, dy),
+            })
+        }
+    }
+
     /// Find a path to the target and return the first point. If this returns `None` then the point
     /// is inaccessible!
-    fn ai_pathfind(&self, pos: Point, world: &World, target: Point) -> Option<Point> {
+    pub fn ai_pathfind(&self, pos: Point, world: &World, target: Point) -> Option<Point> {
         // For laziness, just re-use the existing dijkstra map machinery. This does a huge amount of
         // wasted computation!
-        Map::new(vec![target], world).get_new_pos(target)
+        Map::new(vec![target], world).get_new_pos(pos)
     }
 
     // The `ai_*_commit` functions actually modify the state of the game, and return a simple
     // indicator of success.
 
     /// Move to a new position if possible. Returns `false` if the move cannot go ahead.
-    fn ai_move_commit(&self,
-                      pos: Point,
-                      mobs: &mut BTreeMap<Point, Mobile>,
-                      world: &World,
-                      new_pos: Point)
-                      -> bool {
+    pub fn ai_move_commit(&self,
+                          pos: Point,
+                          mobs: &mut BTreeMap<Point, Mobile>,
+                          world: &World,
+                          new_pos: Point)
+                          -> bool {
         if is_occupied(new_pos, mobs, world) {
             false
         } else {
@@ -202,13 +220,32 @@ impl Mobile {
     }
 
     /// Interact with the given target.
-    fn ai_interact_at_point_commit(&mut self, world: &mut World, target_pos: Point) -> bool {
-        // For now, the only interaction we have is satisfying a desire.
+    pub fn ai_interact_at_point_commit(&mut self,
+                                       maps: &mut Maps,
+                                       world: &mut World,
+                                       target_pos: Point)
+                                       -> bool {
+        // For now, the only interaction we have is satisfying a desire, which wears down the thing
+        // interacted with. Haggling with a customer is good practice for the shopkeeper. Somewhere
+        // which isn't on its heatmap (such as an inn with nobody behind the counter) does nothing,
+        // and one with someone working there does more, the more so the better they are at their
+        // job.
         if let Some(s) = world.statics.at(target_pos) {
             if let Some(tag) = s.maptag() {
+                if !maps.get(tag).sources.contains(&target_pos) {
+                    return false;
+                }
+                let staff = world.staffed_job(target_pos).map(|j| (j.worker, j.skill));
+                let served = 1.0 + staff.map_or(0.0, |(_, skill)| skill as f64 / 10.0);
                 if let Some(old) = self.desires.clone().get(&tag) {
-                    let new = old - 1.0;
+                    let new = old - served;
                     let _ = self.desires.insert(tag, if new < 0.0 { 0.0 } else { new });
+                    let _ = world.use_static(maps, target_pos);
+                    if tag == MapTag::GeneralStore {
+                        if let Some(worker) = staff.and_then(|(worker, _)| worker) {
+                            world.affect(worker, Effect::Practise(Attribute::Bargain, 1));
+                        }
+                    }
                     return true;
                 }
             }
@@ -218,9 +255,37 @@ impl Mobile {
     }
 }
 
-/// Specific tasks that an AI can perform.
-#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
-pub enum Task {
-    /// Move to the given location.
-    MoveTo(Point),
+#[cfg(test)]
+mod tests {
+    use constants::DEFAULT_TRAINING;
+    use grid::Point;
+    use language::Language;
+    use mobiles::Mobile;
+    use mobiles::training::Training;
+    use rand::{SeedableRng, StdRng};
+    use std::cmp;
+    use types::World;
+
+    #[test]
+    fn pathfind_towards_target() {
+        let mut rng: StdRng = SeedableRng::from_seed(&[0][..]);
+        let training = Training::parse(DEFAULT_TRAINING).unwrap();
+        let mut lang = Language::new(&mut rng);
+        let mob = Mobile::gen_adventurer(&mut rng, &training, &mut 0, &mut lang);
+        let world = World::new(lang, training);
+
+        // Diagonal moves cost the same as any other, so this is the number of moves needed.
+        let distance = |a: Point, b: Point| {
+            let d = |x: usize, y: usize| if x > y { x - y } else { y - x };
+            cmp::max(d(a.x, b.x), d(a.y, b.y))
+        };
+        let target = Point { x: 20, y: 15 };
+        let mut pos = Point { x: 5, y: 5 };
+        for _ in 0..distance(pos, target) {
+            let next = mob.ai_pathfind(pos, &world, target).unwrap();
+            assert_eq!(distance(next, target) + 1, distance(pos, target));
+            pos = next;
+        }
+        assert_eq!(pos, target);
+    }
 }
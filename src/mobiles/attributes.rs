@@ -0,0 +1,375 @@
+//! Attributes: how able a mob is at things. See the docs of `Mobile` for the four categories.
+//!
+//! Skill checks combine an attribute with a profession, if the task is being done as part of the
+//! mob's job, and a random roll.
+
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How much each point in a profession adds to a skill check.
+const PROFESSION_BONUS: usize = 2;
+
+/// The size of the die rolled in a skill check.
+const DIE: usize = 20;
+
+/// Things a mob can be more or less able at.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Attribute {
+    // Physical
+    /// Ability to dodge attacks and avoid traps.
+    Agility,
+    /// Ability to recover from poison and disease.
+    Constitution,
+    /// Ability to do more work without needing rest.
+    Endurance,
+    /// Ability to recover while resting.
+    Recuperation,
+    /// Ability to wield weapons and armour.
+    Strength,
+    /// Ability to absorb damage.
+    Toughness,
+
+    // Mental
+    /// Productivity and longevity of animals under the mob's care.
+    Animal,
+    /// Ability to strike a favourable deal.
+    Bargain,
+    /// Ability to leave favourable impressions and make friends.
+    Charm,
+    /// Ability to heal wounds.
+    Chirurgy,
+    /// Ability to repair items.
+    Craft,
+    /// Ability understand others feelings.
+    Empathy,
+    /// Ability to find useful items in the wilderness.
+    Forage,
+    /// Ability to deceive others.
+    Guile,
+    /// Ability to identify healing ingredients and treat disease/poison.
+    Heal,
+    /// Ability to track and trap animals.
+    Hunt,
+    /// Ability to predict events.
+    Intuition,
+    /// Ability to accurately identify and value dungeon spoils.
+    Lore,
+
+    // Competences
+    /// Bonus when using bows.
+    CompetenceBow,
+    /// Bonus when using great weapons.
+    CompetenceGreat,
+    /// Bonus when using shields.
+    CompetenceShield,
+    /// Competence when using single weapons.
+    CompetenceSingle,
+    /// Bonus when spears.
+    CompetenceSpear,
+    /// Bonus when using staves
+    CompetenceStaff,
+    /// Bonus when using swords.
+    CompetenceSword,
+    /// Bonus when using warhammers.
+    CompetenceWarhammer,
+
+    // Professions
+    /// Bonus when acting as a professional adventurer.
+    ProfessionAdventurer,
+    /// Bonus when acting as a professional animal handler.
+    ProfessionAnimalHandler,
+    /// Bonus when acting as a professional apothecarist.
+    ProfessionApothecarist,
+    /// Bonus when acting as a professional appraiser.
+    ProfessionAppraiser,
+    /// Bonus when acting as a professional cutter.
+    ProfessionCutter,
+    /// Bonus when acting as a professional farmer.
+    ProfessionFarmer,
+    /// Bonus when acting as a professional innkeeper.
+    ProfessionInnkeeper,
+    /// Bonus when acting as a professional laborer.
+    ProfessionLaborer,
+    /// Bonus when acting as a professional tinker.
+    ProfessionTinker,
+    /// Bonus when acting as a professional trader.
+    ProfessionTrader,
+    /// Bonus when acting as a professional woodsman.
+    ProfessionWoodsman,
+}
+
+/// The categories of attributes.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    /// Bodily ability.
+    Physical,
+    /// Ability at everyday tasks.
+    Mental,
+    /// Ability with specific types of equipment.
+    Competence,
+    /// General competence in a specific job.
+    Profession,
+}
+
+/// Every attribute, in order.
+pub const ATTRIBUTES: [Attribute; 37] = [Attribute::Agility,
+                                         Attribute::Constitution,
+                                         Attribute::Endurance,
+                                         Attribute::Recuperation,
+                                         Attribute::Strength,
+                                         Attribute::Toughness,
+                                         Attribute::Animal,
+                                         Attribute::Bargain,
+                                         Attribute::Charm,
+                                         Attribute::Chirurgy,
+                                         Attribute::Craft,
+                                         Attribute::Empathy,
+                                         Attribute::Forage,
+                                         Attribute::Guile,
+                                         Attribute::Heal,
+                                         Attribute::Hunt,
+                                         Attribute::Intuition,
+                                         Attribute::Lore,
+                                         Attribute::CompetenceBow,
+                                         Attribute::CompetenceGreat,
+                                         Attribute::CompetenceShield,
+                                         Attribute::CompetenceSingle,
+                                         Attribute::CompetenceSpear,
+                                         Attribute::CompetenceStaff,
+                                         Attribute::CompetenceSword,
+                                         Attribute::CompetenceWarhammer,
+                                         Attribute::ProfessionAdventurer,
+                                         Attribute::ProfessionAnimalHandler,
+                                         Attribute::ProfessionApothecarist,
+                                         Attribute::ProfessionAppraiser,
+                                         Attribute::ProfessionCutter,
+                                         Attribute::ProfessionFarmer,
+                                         Attribute::ProfessionInnkeeper,
+                                         Attribute::ProfessionLaborer,
+                                         Attribute::ProfessionTinker,
+                                         Attribute::ProfessionTrader,
+                                         Attribute::ProfessionWoodsman];
+
+impl Attribute {
+    /// The category this is in.
+    pub fn category(&self) -> Category {
+        match *self {
+            Attribute::Agility | Attribute::Constitution | Attribute::Endurance |
+            Attribute::Recuperation | Attribute::Strength | Attribute::Toughness => {
+                Category::Physical
+            }
+            Attribute::Animal | Attribute::Bargain | Attribute::Charm | Attribute::Chirurgy |
+            Attribute::Craft | Attribute::Empathy | Attribute::Forage | Attribute::Guile |
+            Attribute::Heal | Attribute::Hunt | Attribute::Intuition | Attribute::Lore => {
+                Category::Mental
+            }
+            Attribute::CompetenceBow |
+            Attribute::CompetenceGreat |
+            Attribute::CompetenceShield |
+            Attribute::CompetenceSingle |
+            Attribute::CompetenceSpear |
+            Attribute::CompetenceStaff |
+            Attribute::CompetenceSword |
+            Attribute::CompetenceWarhammer => Category::Competence,
+            Attribute::ProfessionAdventurer |
+            Attribute::ProfessionAnimalHandler |
+            Attribute::ProfessionApothecarist |
+            Attribute::ProfessionAppraiser |
+            Attribute::ProfessionCutter |
+            Attribute::ProfessionFarmer |
+            Attribute::ProfessionInnkeeper |
+            Attribute::ProfessionLaborer |
+            Attribute::ProfessionTinker |
+            Attribute::ProfessionTrader |
+            Attribute::ProfessionWoodsman => Category::Profession,
+        }
+    }
+
+    /// The name used in data files.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Attribute::Agility => "agility",
+            Attribute::Constitution => "constitution",
+            Attribute::Endurance => "endurance",
+            Attribute::Recuperation => "recuperation",
+            Attribute::Strength => "strength",
+            Attribute::Toughness => "toughness",
+            Attribute::Animal => "animal",
+            Attribute::Bargain => "bargain",
+            Attribute::Charm => "charm",
+            Attribute::Chirurgy => "chirurgy",
+            Attribute::Craft => "craft",
+            Attribute::Empathy => "empathy",
+            Attribute::Forage => "forage",
+            Attribute::Guile => "guile",
+            Attribute::Heal => "heal",
+            Attribute::Hunt => "hunt",
+            Attribute::Intuition => "intuition",
+            Attribute::Lore => "lore",
+            Attribute::CompetenceBow => "competence_bow",
+            Attribute::CompetenceGreat => "competence_great",
+            Attribute::CompetenceShield => "competence_shield",
+            Attribute::CompetenceSingle => "competence_single",
+            Attribute::CompetenceSpear => "competence_spear",
+            Attribute::CompetenceStaff => "competence_staff",
+            Attribute::CompetenceSword => "competence_sword",
+            Attribute::CompetenceWarhammer => "competence_warhammer",
+            Attribute::ProfessionAdventurer => "profession_adventurer",
+            Attribute::ProfessionAnimalHandler => "profession_animalhandler",
+            Attribute::ProfessionApothecarist => "profession_apothecarist",
+            Attribute::ProfessionAppraiser => "profession_appraiser",
+            Attribute::ProfessionCutter => "profession_cutter",
+            Attribute::ProfessionFarmer => "profession_farmer",
+            Attribute::ProfessionInnkeeper => "profession_innkeeper",
+            Attribute::ProfessionLaborer => "profession_laborer",
+            Attribute::ProfessionTinker => "profession_tinker",
+            Attribute::ProfessionTrader => "profession_trader",
+            Attribute::ProfessionWoodsman => "profession_woodsman",
+        }
+    }
+
+    /// Look up an attribute by the name used in data files.
+    pub fn from_name(name: &str) -> Option<Attribute> {
+        ATTRIBUTES.iter().find(|a| a.name() == name).cloned()
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.category() {
+            Category::Competence => write!(f, "competence ({})", &self.name()[11..]),
+            Category::Profession => {
+                match *self {
+                    Attribute::ProfessionAnimalHandler => write!(f, "profession (animal handler)"),
+                    _ => write!(f, "profession ({})", &self.name()[11..]),
+                }
+            }
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// The attributes of a mob. Any not explicitly set are zero.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Attributes {
+    /// The non-zero attributes.
+    values: BTreeMap<Attribute, usize>,
+}
+
+impl Attributes {
+    /// Construct a new set of attributes, all zero.
+    pub fn new() -> Attributes {
+        Attributes { values: BTreeMap::new() }
+    }
+
+    /// The value of an attribute.
+    pub fn get(&self, attribute: Attribute) -> usize {
+        self.values.get(&attribute).cloned().unwrap_or(0)
+    }
+
+    /// Set the value of an attribute.
+    pub fn set(&mut self, attribute: Attribute, value: usize) {
+        let _ = self.values.insert(attribute, value);
+    }
+
+    /// Increase an attribute.
+    pub fn add(&mut self, attribute: Attribute, amount: usize) {
+        *self.values.entry(attribute).or_insert(0) += amount;
+    }
+
+    /// Decrease an attribute, stopping at zero.
+    pub fn sub(&mut self, attribute: Attribute, amount: usize) {
+        let value = self.get(attribute).saturating_sub(amount);
+        self.set(attribute, value);
+    }
+
+    /// All the attributes and their values, in order.
+    pub fn iter<'a>(&'a self) -> Iter<'a> {
+        Iter {
+            attributes: self,
+            category: None,
+            next: 0,
+        }
+    }
+
+    /// The attributes in one category and their values, in order.
+    pub fn in_category<'a>(&'a self, category: Category) -> Iter<'a> {
+        Iter {
+            attributes: self,
+            category: Some(category),
+            next: 0,
+        }
+    }
+
+    /// The physical attributes.
+    pub fn physical<'a>(&'a self) -> Iter<'a> {
+        self.in_category(Category::Physical)
+    }
+
+    /// The mental attributes.
+    pub fn mental<'a>(&'a self) -> Iter<'a> {
+        self.in_category(Category::Mental)
+    }
+
+    /// The competences.
+    pub fn competences<'a>(&'a self) -> Iter<'a> {
+        self.in_category(Category::Competence)
+    }
+
+    /// The professions.
+    pub fn professions<'a>(&'a self) -> Iter<'a> {
+        self.in_category(Category::Profession)
+    }
+
+    /// Roll for a task using an attribute: the attribute, plus a bonus for the profession if the
+    /// task is being done as part of the mob's job, plus a die roll.
+    pub fn roll<R: Rng>(&self,
+                        rng: &mut R,
+                        attribute: Attribute,
+                        profession: Option<Attribute>)
+                        -> usize {
+        let bonus = match profession {
+            Some(p) if p.category() == Category::Profession => self.get(p) * PROFESSION_BONUS,
+            Some(p) => panic!("{:?} is not a profession!", p),
+            None => 0,
+        };
+        self.get(attribute) + bonus + rng.gen_range(1, DIE + 1)
+    }
+
+    /// Check whether a roll for a task meets the difficulty.
+    pub fn check<R: Rng>(&self,
+                         rng: &mut R,
+                         attribute: Attribute,
+                         profession: Option<Attribute>,
+                         difficulty: usize)
+                         -> bool {
+        self.roll(rng, attribute, profession) >= difficulty
+    }
+}
+
+/// An iterator over attributes and their values.
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    /// The attributes being iterated over.
+    attributes: &'a Attributes,
+    /// Only produce attributes in this category.
+    category: Option<Category>,
+    /// The index into `ATTRIBUTES` to look at next.
+    next: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Attribute, usize);
+
+    fn next(&mut self) -> Option<(Attribute, usize)> {
+        while self.next < ATTRIBUTES.len() {
+            let attribute = ATTRIBUTES[self.next];
+            self.next += 1;
+            if self.category.map_or(true, |c| attribute.category() == c) {
+                return Some((attribute, self.attributes.get(attribute)));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,233 @@
+//! Behaviour trees, which decide what a mob does each turn. A tree is built from four kinds of
+//! node:
+//!
+//! - A *selector* runs its children in order until one succeeds, and succeeds if any did.
+//! - A *sequence* runs its children in order until one fails, and succeeds if none did.
+//! - A *condition* checks something about the mob, without doing anything.
+//! - An *action* is one of the AI's queries (such as finding a path home) or commits (such as
+//!   moving). Queries leave their result on a blackboard, where later actions can find it.
+//!
+//! So "go home if possible" is a sequence of finding a path home and then moving, and "do the first
+//! thing which works" is a selector. Different archetypes of mob run different trees.
+
+use dijkstra_map::{MapTag, Maps};
+use grid::Point;
+use jobs::Role;
+use mobiles::Mobile;
+use mobiles::training::Stage;
+use rand::Rng;
+use std::collections::BTreeMap;
+use types::World;
+
+/// A node in a behaviour tree.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    /// Run the children in order until one succeeds.
+    Selector(Vec<Node>),
+    /// Run the children in order until one fails.
+    Sequence(Vec<Node>),
+    /// Check a condition.
+    Condition(Condition),
+    /// Do something.
+    Action(Action),
+}
+
+/// Things a behaviour tree can check.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// The mob has a priority task.
+    HasTask,
+    /// The mob has a positive desire for something.
+    Desires(MapTag),
+    /// The mob has a personality trait.
+    HasTrait(&'static str),
+    /// The opposite of another condition.
+    Not(Box<Condition>),
+}
+
+/// Things a behaviour tree can do. Those which find a target put it on the blackboard, and those
+/// which need one fail if there isn't one.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Action {
+    /// Find something adjacent to interact with, to satisfy a desire.
+    FindInteraction,
+    /// Interact with the target.
+    Interact,
+    /// Make progress on the priority task.
+    DoTask,
+    /// Find the next step by following the desire-weighted heatmaps.
+    FollowDesires,
+    /// Find the next step towards the nearest source of a heatmap.
+    Approach(MapTag),
+    /// Find the next step towards home.
+    PathHome,
+    /// Find the next step towards work.
+    PathWork,
+    /// Pick a random adjacent cell.
+    Wander,
+    /// Move to the target.
+    Move,
+    /// Do nothing. This always succeeds.
+    Wait,
+}
+
+/// The kinds of mob, as far as behaviour goes.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Archetype {
+    /// A (non-retired) adventurer.
+    Adventurer,
+    /// Someone working in a store.
+    Shopkeeper,
+    /// A child.
+    Child,
+    /// Anyone else.
+    Townsperson,
+}
+
+/// The part of the world a tree acts on: where the mob is, and everything around them.
+#[derive(Debug)]
+pub struct Context<'a> {
+    /// The position of the mob.
+    pub pos: Point,
+    /// All the mobs.
+    pub mobs: &'a mut BTreeMap<Point, Mobile>,
+    /// The heatmaps.
+    pub maps: &'a mut Maps,
+    /// The world.
+    pub world: &'a mut World,
+}
+
+/// Working state shared between the nodes of a tree while it runs.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Blackboard {
+    /// The target found by the most recent query.
+    pub target: Option<Point>,
+}
+
+impl Archetype {
+    /// The behaviour tree for this archetype.
+    pub fn tree(&self) -> Node {
+        use self::Action::*;
+
+        // Interacting with something nearby, or working on a priority task, come first for
+        // everyone.
+        let interact = Node::Sequence(vec![Node::Action(FindInteraction), Node::Action(Interact)]);
+        let task = Node::Sequence(vec![Node::Condition(Condition::HasTask), Node::Action(DoTask)]);
+        let desires = Node::Sequence(vec![Node::Action(FollowDesires), Node::Action(Move)]);
+        let home = Node::Sequence(vec![Node::Action(PathHome), Node::Action(Move)]);
+
+        match *self {
+            // Adventurers who are itching for adventure head for the dungeon, even without a
+            // plan; otherwise they go where their desires take them, and then go home.
+            Archetype::Adventurer => {
+                let itching = Condition::Desires(MapTag::Adventure);
+                let dungeon = Node::Sequence(vec![Node::Condition(itching),
+                                                  Node::Action(Approach(MapTag::Adventure)),
+                                                  Node::Action(Move)]);
+                Node::Selector(vec![interact, task, dungeon, desires, home, Node::Action(Wait)])
+            }
+            // Shopkeepers who aren't slothful keep an eye on the shop when they've nothing better
+            // to do, rather than going home.
+            Archetype::Shopkeeper => {
+                let diligent = Condition::Not(Box::new(Condition::HasTrait("slothful")));
+                let shop = Node::Sequence(vec![Node::Condition(diligent),
+                                               Node::Action(PathWork),
+                                               Node::Action(Move)]);
+                Node::Selector(vec![interact, task, desires, shop, home, Node::Action(Wait)])
+            }
+            // Townspeople go where their desires take them, and then go home.
+            Archetype::Townsperson => {
+                Node::Selector(vec![interact, task, desires, home, Node::Action(Wait)])
+            }
+            // Children don't have a care in the world, and play rather than going home.
+            Archetype::Child => {
+                let play = Node::Sequence(vec![Node::Action(Wander), Node::Action(Move)]);
+                Node::Selector(vec![interact, desires, play, Node::Action(Wait)])
+            }
+        }
+    }
+}
+
+impl Mobile {
+    /// The archetype of the mob.
+    pub fn archetype(&self) -> Archetype {
+        if self.onset_age.is_some() && self.retirement_age.is_none() {
+            Archetype::Adventurer
+        } else if self.job == Some(Role::Shopkeeper) {
+            Archetype::Shopkeeper
+        } else if self.stage() == Stage::Youth {
+            Archetype::Child
+        } else {
+            Archetype::Townsperson
+        }
+    }
+
+    /// Run a behaviour tree for one turn. Returns whether it succeeded.
+    pub fn run_tree<R: Rng>(&mut self,
+                            rng: &mut R,
+                            node: &Node,
+                            blackboard: &mut Blackboard,
+                            ctx: &mut Context)
+                            -> bool {
+        match *node {
+            Node::Selector(ref children) => {
+                for child in children {
+                    if self.run_tree(rng, child, blackboard, ctx) {
+                        return true;
+                    }
+                }
+                false
+            }
+            Node::Sequence(ref children) => {
+                for child in children {
+                    if !self.run_tree(rng, child, blackboard, ctx) {
+                        return false;
+                    }
+                }
+                true
+            }
+            Node::Condition(ref condition) => self.check_condition(condition),
+            Node::Action(action) => self.run_action(rng, action, blackboard, ctx),
+        }
+    }
+
+    /// Check a condition.
+    fn check_condition(&self, condition: &Condition) -> bool {
+        match *condition {
+            Condition::HasTask => self.priority_task.is_some(),
+            Condition::Desires(tag) => self.desires.get(&tag).map_or(false, |&w| w > 0.0),
+            Condition::HasTrait(name) => self.personality_trait(name) == Some(true),
+            Condition::Not(ref condition) => !self.check_condition(condition),
+        }
+    }
+
+    /// Do an action.
+    fn run_action<R: Rng>(&mut self,
+                          rng: &mut R,
+                          action: Action,
+                          blackboard: &mut Blackboard,
+                          ctx: &mut Context)
+                          -> bool {
+        let pos = ctx.pos;
+        let found = match action {
+            Action::FindInteraction => self.ai_interact_nearby(pos, ctx.maps),
+            Action::DoTask => return self.ai_do_task(rng, pos, ctx.mobs, ctx.maps, ctx.world),
+            Action::FollowDesires => self.ai_heatmap_wsum(pos, ctx.maps, ctx.world),
+            Action::Approach(tag) => self.ai_approach(pos, ctx.maps, tag),
+            Action::PathHome => self.ai_pathfind(pos, ctx.world, self.home_pos),
+            Action::PathWork => self.workplace.and_then(|w| self.ai_pathfind(pos, ctx.world, w)),
+            Action::Wander => self.ai_wander(rng, pos),
+            Action::Interact => {
+                return blackboard.target
+                    .map_or(false, |t| self.ai_interact_at_point_commit(ctx.maps, ctx.world, t))
+            }
+            Action::Move => {
+                return blackboard.target
+                    .map_or(false, |t| self.ai_move_commit(pos, ctx.mobs, ctx.world, t))
+            }
+            Action::Wait => return true,
+        };
+        blackboard.target = found;
+        found.is_some()
+    }
+}
@@ -0,0 +1,283 @@
+//! Biographies: a mob's history, personality, and origins, written out as prose. The history is a
+//! list of terse events ("learned Farmer at 19"), which is fine for the simulation but not much fun
+//! to read. A biography has three paragraphs:
+//!
+//! 1. Their upbringing and career: where they were born, and to whom; their childhood; and the
+//!    training packages they took, grouped by stage of life, with consecutive years of the same
+//!    package run together.
+//! 2. Everything else which has happened to them, in order.
+//! 3. Who they are now.
+//!
+//! How each training package reads is given in the training data file. Mobs are always "they", as
+//! there is nothing else to go on.
+
+use mobiles::{LifeEvent, Mobile, PERSONALITY_TRAITS};
+use mobiles::training::{Stage, Training};
+
+impl Mobile {
+    /// Write the mob's biography. Other mobs mentioned in the history are named by looking them up
+    /// by id with `name_of`; if that gives `None`, they are referred to by their relationship.
+    /// Paragraphs are separated by a blank line.
+    pub fn biography<F>(&self, training: &Training, name_of: F) -> String
+        where F: Fn(usize) -> Option<String>
+    {
+        let paragraphs = vec![self.upbringing(training, &name_of),
+                              self.life_events(training, &name_of),
+                              self.temperament()];
+        paragraphs.into_iter().filter(|p| !p.is_empty()).collect::<Vec<_>>().join("\n\n")
+    }
+
+    /// The first paragraph: upbringing and career.
+    fn upbringing<F>(&self, training: &Training, name_of: &F) -> String
+        where F: Fn(usize) -> Option<String>
+    {
+        let mut sentences = Vec::new();
+
+        // Birth.
+        let parents: Vec<String> = self.history
+            .iter()
+            .filter_map(|&(_, ref event)| match *event {
+                LifeEvent::BornTo { parent } => Some(name_of(parent).unwrap_or("a parent".into())),
+                _ => None,
+            })
+            .collect();
+        let mut born = format!("{} was born in {}", self.name, self.birthplace);
+        if let Some(ref origin) = self.origin {
+            born.push_str(&format!(", in the far land of {}", origin));
+        }
+        if !parents.is_empty() {
+            born.push_str(&format!(", to {}", list(&parents)));
+        }
+        sentences.push(born);
+
+        // Training, grouped into stages of life, with runs of the same package merged.
+        let mut stages: Vec<(Stage, Vec<(String, usize)>)> = Vec::new();
+        for &(age, ref event) in &self.history {
+            let (stage, prose, years) = match *event {
+                LifeEvent::Raised { ref childhood } => {
+                    let prose = training.get(childhood)
+                        .map_or(format!("a {} childhood", childhood.to_lowercase()),
+                                |p| p.prose());
+                    (Stage::Childhood, prose, 0)
+                }
+                LifeEvent::Learned { ref package } => {
+                    match training.get(package) {
+                        Some(p) => (self.stage_at(age - p.years), p.prose(), p.years),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            if stages.last().map_or(true, |&(s, _)| s != stage) {
+                stages.push((stage, Vec::new()));
+            }
+            let runs = &mut stages.last_mut().unwrap().1;
+            if runs.last().map_or(false, |&(ref p, _)| *p == prose) {
+                runs.last_mut().unwrap().1 += years;
+            } else {
+                runs.push((prose, years));
+            }
+        }
+
+        let mut had_onset = false;
+        for (stage, runs) in stages {
+            let spent: Vec<String> = runs.iter()
+                .map(|&(ref prose, years)| format!("{} {}", duration(years), prose))
+                .collect();
+            sentences.push(match stage {
+                Stage::Childhood => format!("They had {}", runs[0].0),
+                Stage::Youth => format!("As a youth, they spent {}", list(&spent)),
+                Stage::PreOnset => format!("As an adult, they spent {}", list(&spent)),
+                Stage::PostOnset => {
+                    had_onset = true;
+                    format!("At {} they took up adventuring, and since then have spent {}",
+                            self.onset_age.unwrap_or(0),
+                            list(&spent))
+                }
+            });
+        }
+
+        // Those who have only just become adventurers have no post-onset packages.
+        if !had_onset && self.is_adventurer() {
+            sentences.push(format!("At {} they took up adventuring",
+                                   self.onset_age.unwrap_or(self.age)));
+        }
+
+        // Retirement.
+        for &(age, ref event) in &self.history {
+            if let LifeEvent::Retired { ref package } = *event {
+                let prose = training.get(package)
+                    .map_or(format!("as a {}", package.to_lowercase()), |p| p.prose());
+                sentences.push(format!("At {} they gave up adventuring, to spend their days {}",
+                                       age,
+                                       prose));
+            }
+        }
+
+        sentences.iter().map(|s| format!("{}.", s)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// The second paragraph: everything which has happened, other than training.
+    fn life_events<F>(&self, training: &Training, name_of: &F) -> String
+        where F: Fn(usize) -> Option<String>
+    {
+        let mut sentences: Vec<String> = Vec::new();
+        let mut last_age = None;
+        let mut births: Option<(usize, &'static str, usize, Vec<String>)> = None;
+        for &(age, ref event) in &self.history {
+            match *event {
+                LifeEvent::HadChild { .. } |
+                LifeEvent::SiblingBorn { .. } => (),
+                _ => births = None,
+            }
+            let happened = match *event {
+                LifeEvent::Married { spouse } => {
+                    match name_of(spouse) {
+                        Some(n) => format!("married {}", n),
+                        None => "married".to_string(),
+                    }
+                }
+                // Children (or siblings) born in the same year are mentioned together.
+                LifeEvent::HadChild { child: id } |
+                LifeEvent::SiblingBorn { sibling: id } => {
+                    let kind = event.kind_name();
+                    let (mut count, mut names) = (1, Vec::new());
+                    if let Some((a, k, c, ref ns)) = births {
+                        if a == age && k == kind {
+                            let _ = sentences.pop();
+                            count = c + 1;
+                            names = ns.clone();
+                        }
+                    }
+                    names.extend(name_of(id));
+                    births = Some((age, kind, count, names.clone()));
+                    let (verb, one, many) = match *event {
+                        LifeEvent::HadChild { .. } => ("had", "a child", "children"),
+                        _ => ("gained", "a sibling", "siblings"),
+                    };
+                    let what = if count == 1 {
+                        one.to_string()
+                    } else {
+                        format!("{} {}", number(count), many)
+                    };
+                    if names.is_empty() {
+                        format!("{} {}", verb, what)
+                    } else {
+                        format!("{} {}, {}", verb, what, list(&names))
+                    }
+                }
+                LifeEvent::Practised { ref package } => {
+                    let prose = training.get(package)
+                        .map_or(format!("training as {}", package.to_lowercase()),
+                                |p| p.prose());
+                    format!("had learned as much through practice as they might have by spending \
+                             years {}",
+                            prose)
+                }
+                LifeEvent::NearDeath => "nearly died in the dungeon".to_string(),
+                LifeEvent::GotRich => "came into money".to_string(),
+                LifeEvent::LostFriend { friend } => {
+                    match name_of(friend) {
+                        Some(n) => format!("lost a friend, {}", n),
+                        None => "lost a friend".to_string(),
+                    }
+                }
+                LifeEvent::Robbed => "were robbed".to_string(),
+
+                // A change of personality follows whatever caused it, if anything did.
+                LifeEvent::Changed { ref name, has } => {
+                    let change = if has {
+                        format!("became {}", name)
+                    } else {
+                        format!("stopped being {}", name)
+                    };
+                    match sentences.last_mut() {
+                        Some(s) if last_age == Some(age) => {
+                            s.push_str(&format!(", and {}", change));
+                            continue;
+                        }
+                        _ => change,
+                    }
+                }
+                _ => continue,
+            };
+            sentences.push(format!("At {} they {}", age, happened));
+            last_age = Some(age);
+        }
+
+        sentences.iter().map(|s| format!("{}.", s)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// The third paragraph: who the mob is now.
+    fn temperament(&self) -> String {
+        let traits: Vec<String> = PERSONALITY_TRAITS.iter()
+            .filter(|name| self.personality_trait(name) == Some(true))
+            .map(|name| name.to_string())
+            .collect();
+        let occupation = if let Some(job) = self.job {
+            format!("working as {}", with_article(job.name()))
+        } else if self.retirement_age.is_some() {
+            "a retired adventurer".to_string()
+        } else if self.is_adventurer() {
+            "an adventurer".to_string()
+        } else if self.stage() == Stage::Youth {
+            "a child".to_string()
+        } else {
+            "an ordinary townsperson".to_string()
+        };
+        if traits.is_empty() {
+            format!("Now {} and {}, there is nothing remarkable about their temperament.",
+                    self.age,
+                    occupation)
+        } else {
+            format!("Now {} and {}, they are {}.", self.age, occupation, list(&traits))
+        }
+    }
+
+    /// Whether the mob has become an adventurer, even if they have since retired.
+    fn is_adventurer(&self) -> bool {
+        self.history.iter().any(|&(_, ref event)| match *event {
+            LifeEvent::Onset => true,
+            _ => false,
+        })
+    }
+}
+
+/// Small numbers, in words.
+const NUMBERS: [&'static str; 13] = ["no", "one", "two", "three", "four", "five", "six", "seven",
+                                     "eight", "nine", "ten", "eleven", "twelve"];
+
+/// A number, in words if it's small.
+fn number(n: usize) -> String {
+    if n < NUMBERS.len() {
+        NUMBERS[n].to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+/// A number of years, in words.
+fn duration(years: usize) -> String {
+    match years {
+        1 => "a year".to_string(),
+        n => format!("{} years", number(n)),
+    }
+}
+
+/// Join a list of phrases: "a", "a and b", or "a, b, and c".
+fn list(items: &[String]) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        2 => format!("{} and {}", items[0], items[1]),
+        n => format!("{}, and {}", items[..n - 1].join(", "), items[n - 1]),
+    }
+}
+
+/// A noun with "a" or "an" in front, as appropriate.
+fn with_article(noun: &str) -> String {
+    match noun.chars().next() {
+        Some('a') | Some('e') | Some('i') | Some('o') | Some('u') => format!("an {}", noun),
+        _ => format!("a {}", noun),
+    }
+}
@@ -0,0 +1,233 @@
+//! Constraints on generated mobs, for designing scenarios and testing balance. Some constraints
+//! (the kind of mob, their age, and their personality) are imposed during generation; the rest (the
+//! training packages they've taken, and filters on their attributes) are checked afterwards, and
+//! mobs are generated until one passes.
+
+use language::Language;
+use mobiles::Mobile;
+use mobiles::attributes::Attribute;
+use mobiles::gen::MobKind;
+use mobiles::training::Training;
+use rand::Rng;
+
+/// How many mobs to generate looking for one which satisfies the constraints, before giving up.
+pub const MAX_ATTEMPTS: usize = 10000;
+
+/// The comparison operators, longest first so that `>=` isn't mistaken for `>`.
+const OPERATORS: [(&'static str, Comparison); 7] = [(">=", Comparison::Ge),
+                                                    ("<=", Comparison::Le),
+                                                    ("==", Comparison::Eq),
+                                                    ("!=", Comparison::Ne),
+                                                    (">", Comparison::Gt),
+                                                    ("<", Comparison::Lt),
+                                                    ("=", Comparison::Eq)];
+
+/// Ways of comparing an attribute to a value.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Comparison {
+    /// Less than.
+    Lt,
+    /// Less than or equal.
+    Le,
+    /// Equal.
+    Eq,
+    /// Not equal.
+    Ne,
+    /// Greater than or equal.
+    Ge,
+    /// Greater than.
+    Gt,
+}
+
+/// A filter on the value of an attribute, such as `profession_appraiser >= 10`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Filter {
+    /// The attribute to check.
+    pub attribute: Attribute,
+    /// How to compare it.
+    pub comparison: Comparison,
+    /// What to compare it to.
+    pub value: usize,
+}
+
+/// Constraints on a mob. The defaults leave everything random.
+#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Constraints {
+    /// The kind of mob, or `None` to pick one at random.
+    pub kind: Option<MobKind>,
+    /// The age of the mob.
+    pub age: Option<usize>,
+    /// Personality traits, by name, and whether the mob has them.
+    pub traits: Vec<(String, bool)>,
+    /// Training packages (or childhoods) the mob must have taken.
+    pub packages: Vec<String>,
+    /// Filters the mob's attributes must pass.
+    pub filters: Vec<Filter>,
+}
+
+impl Comparison {
+    /// Compare two values.
+    pub fn compare(&self, a: usize, b: usize) -> bool {
+        match *self {
+            Comparison::Lt => a < b,
+            Comparison::Le => a <= b,
+            Comparison::Eq => a == b,
+            Comparison::Ne => a != b,
+            Comparison::Ge => a >= b,
+            Comparison::Gt => a > b,
+        }
+    }
+}
+
+impl Filter {
+    /// Parse a filter of the form `attribute op value`, where the operator is one of `<`, `<=`,
+    /// `=` (or `==`), `!=`, `>=`, or `>`. Spaces around the operator are optional.
+    pub fn parse(input: &str) -> Result<Filter, String> {
+        for &(op, comparison) in &OPERATORS {
+            if let Some(i) = input.find(op) {
+                let name = input[..i].trim();
+                let value = input[i + op.len()..].trim();
+                let attribute = try!(Attribute::from_name(name)
+                    .ok_or(format!("Unknown attribute {:?}.", name)));
+                let value = try!(value.parse().map_err(|_| format!("Bad value {:?}.", value)));
+                return Ok(Filter {
+                    attribute: attribute,
+                    comparison: comparison,
+                    value: value,
+                });
+            }
+        }
+        Err(format!("No comparison in {:?}.", input))
+    }
+
+    /// Whether the mob passes the filter.
+    pub fn matches(&self, mob: &Mobile) -> bool {
+        self.comparison.compare(mob.attributes.get(self.attribute), self.value)
+    }
+}
+
+impl Constraints {
+    /// Check that the constraints can be satisfied at all: that the age is in range for the kind,
+    /// that the traits and packages exist, and that adventurers aren't required to be cowards.
+    pub fn check(&self, training: &Training) -> Result<(), String> {
+        if let (Some(kind), Some(age)) = (self.kind, self.age) {
+            let (min, max) = kind.age_range();
+            if age < min || max.map_or(false, |m| age > m) {
+                return Err(format!("A {:?} can't be {} years old.", kind, age));
+            }
+        }
+        for &(ref name, has) in &self.traits {
+            if !Mobile::is_personality_trait(name) {
+                return Err(format!("Unknown personality trait {:?}.", name));
+            }
+            if name == "brave" && !has && self.kind == Some(MobKind::Adventurer) {
+                return Err("Adventurers are always brave.".to_string());
+            }
+        }
+        for name in &self.packages {
+            if training.get(name).is_none() {
+                return Err(format!("Unknown training package {:?}.", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the mob satisfies the constraints which are checked after generation.
+    pub fn satisfied_by(&self, mob: &Mobile) -> bool {
+        self.packages.iter().all(|p| mob.has_learned(p)) &&
+        self.filters.iter().all(|f| f.matches(mob))
+    }
+
+    /// Pick a kind which fits the other constraints: if no kind was given, one is picked at random
+    /// from those which can be the required age and have the required personality.
+    fn pick_kind<R: Rng>(&self, rng: &mut R) -> Option<MobKind> {
+        if self.kind.is_some() {
+            return self.kind;
+        }
+        let kinds: Vec<MobKind> = [MobKind::Adventurer, MobKind::Child, MobKind::Adult]
+            .iter()
+            .cloned()
+            .filter(|kind| {
+                let (min, max) = kind.age_range();
+                let age_ok = self.age.map_or(true, |a| a >= min && max.map_or(true, |m| a <= m));
+                let brave_ok = *kind != MobKind::Adventurer ||
+                               !self.traits.iter().any(|&(ref n, has)| n == "brave" && !has);
+                age_ok && brave_ok
+            })
+            .collect();
+        rng.choose(&kinds).cloned()
+    }
+}
+
+impl Mobile {
+    /// Generate a mob satisfying the constraints, which are assumed to have been checked. Returns
+    /// the kind of the mob too, as that may have been picked at random. Gives up after
+    /// `MAX_ATTEMPTS` tries, returning `None`.
+    pub fn gen_constrained<R: Rng>(rng: &mut R,
+                                   training: &Training,
+                                   constraints: &Constraints,
+                                   next_id: &mut usize,
+                                   lang: &mut Language)
+                                   -> Option<(MobKind, Mobile)> {
+        for _ in 0..MAX_ATTEMPTS {
+            let kind = match constraints.pick_kind(rng) {
+                Some(kind) => kind,
+                None => return None,
+            };
+            let mob = Mobile::gen_kind(rng,
+                                       training,
+                                       kind,
+                                       constraints.age,
+                                       &constraints.traits,
+                                       next_id,
+                                       lang);
+            if constraints.satisfied_by(&mob) {
+                return Some((kind, mob));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mobiles::attributes::Attribute;
+    use super::{Comparison, Filter};
+
+    /// Parse a filter on `lore`, which should succeed.
+    fn parse_lore(input: &str) -> (Comparison, usize) {
+        let filter = Filter::parse(input).unwrap();
+        assert_eq!(filter.attribute, Attribute::Lore);
+        (filter.comparison, filter.value)
+    }
+
+    #[test]
+    fn parse_each_operator() {
+        for &(op, comparison) in &[("<", Comparison::Lt),
+                                   ("<=", Comparison::Le),
+                                   ("=", Comparison::Eq),
+                                   ("==", Comparison::Eq),
+                                   ("!=", Comparison::Ne),
+                                   (">=", Comparison::Ge),
+                                   (">", Comparison::Gt)] {
+            assert_eq!(parse_lore(&format!("lore {} 10", op)), (comparison, 10));
+            assert_eq!(parse_lore(&format!("lore{}10", op)), (comparison, 10));
+        }
+    }
+
+    #[test]
+    fn parse_unknown_attribute() {
+        assert!(Filter::parse("wisdom >= 10").unwrap_err().contains("Unknown attribute"));
+    }
+
+    #[test]
+    fn parse_bad_value() {
+        assert!(Filter::parse("lore >= ten").unwrap_err().contains("Bad value"));
+        assert!(Filter::parse("lore >= -1").unwrap_err().contains("Bad value"));
+    }
+
+    #[test]
+    fn parse_no_operator() {
+        assert!(Filter::parse("lore 10").unwrap_err().contains("No comparison"));
+    }
+}
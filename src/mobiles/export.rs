@@ -0,0 +1,180 @@
+//! Machine-readable output of mobs, for piping generated populations into spreadsheets and balance
+//! scripts. There are two formats:
+//!
+//! - JSON, with one object per mob, giving everything: attributes, traits, and history.
+//! - CSV, with one row per mob. Every attribute and trait gets a column, and the history is packed
+//!   into a single column as `age:event:detail` entries separated by semicolons.
+//!
+//! Other mobs (family and friends) are referred to by id in both.
+
+use mobiles::{LifeEvent, Mobile, PERSONALITY_TRAITS};
+use mobiles::attributes::ATTRIBUTES;
+use utils::{csv_field, json_string};
+
+impl LifeEvent {
+    /// A short name for the kind of event.
+    pub fn kind_name(&self) -> &'static str {
+        match *self {
+            LifeEvent::Born => "born",
+            LifeEvent::Raised { .. } => "raised",
+            LifeEvent::Learned { .. } => "learned",
+            LifeEvent::Onset => "onset",
+            LifeEvent::BornTo { .. } => "born_to",
+            LifeEvent::Married { .. } => "married",
+            LifeEvent::HadChild { .. } => "had_child",
+            LifeEvent::SiblingBorn { .. } => "sibling_born",
+            LifeEvent::Retired { .. } => "retired",
+            LifeEvent::Practised { .. } => "practised",
+            LifeEvent::NearDeath => "near_death",
+            LifeEvent::GotRich => "got_rich",
+            LifeEvent::LostFriend { .. } => "lost_friend",
+            LifeEvent::Robbed => "robbed",
+            LifeEvent::Changed { has: true, .. } => "gained_trait",
+            LifeEvent::Changed { has: false, .. } => "lost_trait",
+        }
+    }
+
+    /// The detail of the event, if it has one: a package, childhood, trait, or mob id.
+    pub fn detail(&self) -> Option<String> {
+        match *self {
+            LifeEvent::Raised { childhood: ref name } |
+            LifeEvent::Learned { package: ref name } |
+            LifeEvent::Retired { package: ref name } |
+            LifeEvent::Practised { package: ref name } |
+            LifeEvent::Changed { ref name, .. } => Some(name.clone()),
+            LifeEvent::BornTo { parent: id } |
+            LifeEvent::Married { spouse: id } |
+            LifeEvent::HadChild { child: id } |
+            LifeEvent::SiblingBorn { sibling: id } |
+            LifeEvent::LostFriend { friend: id } => Some(id.to_string()),
+            LifeEvent::Born | LifeEvent::Onset | LifeEvent::NearDeath | LifeEvent::GotRich |
+            LifeEvent::Robbed => None,
+        }
+    }
+
+    /// The key the detail is given under in JSON.
+    fn detail_key(&self) -> &'static str {
+        match *self {
+            LifeEvent::Raised { .. } => "childhood",
+            LifeEvent::Learned { .. } |
+            LifeEvent::Retired { .. } |
+            LifeEvent::Practised { .. } => "package",
+            LifeEvent::Changed { .. } => "trait",
+            LifeEvent::BornTo { .. } => "parent",
+            LifeEvent::Married { .. } => "spouse",
+            LifeEvent::HadChild { .. } => "child",
+            LifeEvent::SiblingBorn { .. } => "sibling",
+            LifeEvent::LostFriend { .. } => "friend",
+            _ => "detail",
+        }
+    }
+}
+
+impl Mobile {
+    /// The mob as a JSON object. The type is a description of the mob, such as "Adventurer".
+    pub fn to_json(&self, ty: &str) -> String {
+        let traits: Vec<String> = PERSONALITY_TRAITS.iter()
+            .map(|name| format!("{}:{}", json_string(name), self.personality_trait(name).unwrap()))
+            .collect();
+        let attributes: Vec<String> = self.attributes
+            .iter()
+            .map(|(attribute, value)| format!("{}:{}", json_string(attribute.name()), value))
+            .collect();
+        let history: Vec<String> = self.history
+            .iter()
+            .map(|&(age, ref event)| {
+                let detail = match event.detail() {
+                    Some(detail) => {
+                        match *event {
+                            // Ids are numbers, everything else is a string.
+                            LifeEvent::BornTo { .. } |
+                            LifeEvent::Married { .. } |
+                            LifeEvent::HadChild { .. } |
+                            LifeEvent::SiblingBorn { .. } |
+                            LifeEvent::LostFriend { .. } => {
+                                format!(",{}:{}", json_string(event.detail_key()), detail)
+                            }
+                            _ => {
+                                format!(",{}:{}",
+                                        json_string(event.detail_key()),
+                                        json_string(&detail))
+                            }
+                        }
+                    }
+                    None => String::new(),
+                };
+                format!("{{\"age\":{},\"event\":{}{}}}",
+                        age,
+                        json_string(event.kind_name()),
+                        detail)
+            })
+            .collect();
+
+        format!("{{\"id\":{},\"type\":{},\"name\":{},\"origin\":{},\"birthplace\":{},\
+                 \"age\":{},\"birthday\":{},\"onset_age\":{},\"retirement_age\":{},\
+                 \"traits\":{{{}}},\"attributes\":{{{}}},\"history\":[{}]}}",
+                self.id,
+                json_string(ty),
+                json_string(&self.name),
+                self.origin.as_ref().map_or("null".to_string(), |o| json_string(o)),
+                json_string(&self.birthplace),
+                self.age,
+                self.birthday,
+                json_optional(self.onset_age),
+                json_optional(self.retirement_age),
+                traits.join(","),
+                attributes.join(","),
+                history.join(","))
+    }
+
+    /// The header row for CSV output.
+    pub fn csv_header() -> String {
+        let mut columns: Vec<String> = ["id",
+                                        "type",
+                                        "name",
+                                        "origin",
+                                        "birthplace",
+                                        "age",
+                                        "birthday",
+                                        "onset_age",
+                                        "retirement_age"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        columns.extend(PERSONALITY_TRAITS.iter().map(|t| t.to_string()));
+        columns.extend(ATTRIBUTES.iter().map(|a| a.name().to_string()));
+        columns.push("history".to_string());
+        columns.join(",")
+    }
+
+    /// The mob as a CSV row, with the columns given by `csv_header`. Missing values are empty.
+    pub fn to_csv(&self, ty: &str) -> String {
+        let optional = |v: Option<usize>| v.map_or(String::new(), |v| v.to_string());
+        let mut columns = vec![self.id.to_string(),
+                               csv_field(ty),
+                               csv_field(&self.name),
+                               self.origin.as_ref().map_or(String::new(), |o| csv_field(o)),
+                               csv_field(&self.birthplace),
+                               self.age.to_string(),
+                               self.birthday.to_string(),
+                               optional(self.onset_age),
+                               optional(self.retirement_age)];
+        columns.extend(PERSONALITY_TRAITS.iter()
+            .map(|name| self.personality_trait(name).unwrap().to_string()));
+        columns.extend(ATTRIBUTES.iter().map(|&a| self.attributes.get(a).to_string()));
+        let history: Vec<String> = self.history
+            .iter()
+            .map(|&(age, ref event)| match event.detail() {
+                Some(detail) => format!("{}:{}:{}", age, event.kind_name(), detail),
+                None => format!("{}:{}", age, event.kind_name()),
+            })
+            .collect();
+        columns.push(csv_field(&history.join(";")));
+        columns.join(",")
+    }
+}
+
+/// A number which might be missing, as JSON.
+fn json_optional(value: Option<usize>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
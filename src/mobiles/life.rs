@@ -0,0 +1,66 @@
+//! Life events which happen once the game is underway. These go into the mob's history like the
+//! ones from generation, but can also change their personality: someone who narrowly escapes death
+//! might lose their nerve, and someone who has been robbed might start to hold their money a little
+//! tighter.
+//!
+//! Each kind of event has some rules, saying which traits it can give or take away, and how likely
+//! that is. A mob can change in more than one way from a single event.
+
+use mobiles::{LifeEvent, Mobile};
+use rand::Rng;
+
+/// A way an event can change personality: the trait, whether the mob gains (`true`) or loses
+/// (`false`) it, and the chance of that happening.
+pub type Rule = (&'static str, bool, f64);
+
+impl LifeEvent {
+    /// The ways this event can change personality.
+    pub fn personality_rules(&self) -> Vec<Rule> {
+        match *self {
+            // Facing death can go either way.
+            LifeEvent::NearDeath => {
+                vec![("brave", false, 0.4), ("brave", true, 0.2), ("slothful", true, 0.2)]
+            }
+            LifeEvent::GotRich => {
+                vec![("avaricious", true, 0.3), ("envious", false, 0.5), ("gluttonous", true, 0.2)]
+            }
+            LifeEvent::LostFriend { .. } => {
+                vec![("brave", false, 0.2), ("gluttonous", true, 0.2), ("slothful", true, 0.3)]
+            }
+            LifeEvent::Robbed => vec![("avaricious", true, 0.5), ("envious", true, 0.3)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Mobile {
+    /// Something has happened to the mob. Record it in their history, at their current age, and
+    /// apply its rules to their personality; any changes are recorded too. Rules only apply if the
+    /// mob doesn't already have (or lack) the trait, and are all checked against their personality
+    /// from before the event. Returns `true` if their personality changed.
+    pub fn experience<R: Rng>(&mut self, rng: &mut R, event: LifeEvent) -> bool {
+        let age = self.age;
+        let rules = event.personality_rules();
+        self.add_history(age, event);
+
+        let mut changes = Vec::new();
+        for (name, has, chance) in rules {
+            if self.personality_trait(name) == Some(!has) && rng.next_f64() < chance {
+                changes.push((name, has));
+            }
+        }
+
+        let changed = !changes.is_empty();
+        for (name, has) in changes {
+            if let Some(t) = self.personality_trait_mut(name) {
+                *t = has;
+            }
+            self.add_history(age,
+                             LifeEvent::Changed {
+                                 name: name.to_string(),
+                                 has: has,
+                             });
+        }
+        changed
+    }
+}
@@ -1,14 +1,29 @@
 //! Mobiles: things which can move around the world. Citizens, animals, visitors, and monsters all
 //! fall into this class.
 
+pub mod ageing;
 pub mod ai;
+pub mod attributes;
+pub mod behaviour;
+pub mod biography;
+pub mod constraints;
+pub mod export;
 pub mod gen;
+pub mod life;
+pub mod practice;
+pub mod schedule;
+pub mod skills;
+pub mod tasks;
+pub mod training;
 
 use constants::*;
 use dijkstra_map::*;
 use grid::*;
-use mobiles::ai::Task;
-use mobiles::gen::{Childhood, TrainingPackage};
+use jobs::Role;
+use mobiles::attributes::{Attribute, Attributes};
+use mobiles::schedule::{Activity, Schedule};
+use mobiles::tasks::Task;
+use rand::Rng;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::f64;
@@ -17,11 +32,11 @@ use utils::*;
 
 /// Things which roam around in the world, like people and monsters.
 ///
-/// Mobs have biography, desires, personality traits, and attributes.
+/// Mobs have biography, desires, personality traits, attributes, and possessions.
 ///
-/// - Biography has no effect on the game and is just flavour. The age and onset age affect
-///   character generation, but not how the character then behaves once generated. Age might become
-///   important later, once I implement the passage of in-world time.
+/// - Biography is mostly flavour. The age and onset age affect character generation, and age
+///   keeps counting up in play: on their birthday each year the old decline, adventurers may
+///   retire, and eventually everyone dies.
 ///
 /// - Desires are what the mob wants to do *right now*. The relative weight of the desires
 ///   determines in part what it will do next.
@@ -43,12 +58,22 @@ use utils::*;
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct Mobile {
     // Biography
+    /// A unique identifier, used to refer to this mob from elsewhere in the world.
+    pub id: usize,
     /// The name. This doesn't affect anything, and is just flavour.
     pub name: String,
+    /// The name of the foreign land the mob comes from, if they're not a local.
+    pub origin: Option<String>,
+    /// The name of the town or village the mob was born in.
+    pub birthplace: String,
     /// How old the mob is.
     pub age: usize,
+    /// The day of the year on which the mob was born.
+    pub birthday: usize,
     /// Age at which the mob became an adventurer, if they are one.
     pub onset_age: Option<usize>,
+    /// Age at which the mob retired from adventuring, if they have.
+    pub retirement_age: Option<usize>,
     /// The developmental history of this mob. The `usize` is the age at which this happened. This
     /// is sorted ascending by year.
     pub history: Vec<(usize, LifeEvent)>,
@@ -60,6 +85,12 @@ pub struct Mobile {
     pub desires: BTreeMap<MapTag, f64>,
     /// The location of the mob's home. This is where it returns when there is nothing else to do.
     pub home_pos: Point,
+    /// The mob's job, if they have one.
+    pub job: Option<Role>,
+    /// The location of the mob's work, if they have a job.
+    pub workplace: Option<Point>,
+    /// The mob's daily routine.
+    pub schedule: Schedule,
 
     // Personality traits
     /// Increases the value the mob ascribes to items it is trying to sell.
@@ -73,110 +104,196 @@ pub struct Mobile {
     /// Increases the minimum rest desire weighting.
     pub is_slothful: bool,
 
-    // Attributes (physical)
-    /// Ability to dodge attacks and avoid traps.
-    pub agility: usize,
-    /// Ability to recover from poison and disease.
-    pub constitution: usize,
-    /// Ability to do more work without needing rest.
-    pub endurance: usize,
-    /// Ability to recover while resting.
-    pub recuperation: usize,
-    /// Ability to wield weapons and armour.
-    pub strength: usize,
-    /// Ability to absorb damage.
-    pub toughness: usize,
-
-    // Attributes (mental)
-    /// Productivity and longevity of animals under the mob's care.
-    pub animal: usize,
-    /// Ability to strike a favourable deal.
-    pub bargain: usize,
-    /// Ability to leave favourable impressions and make friends.
-    pub charm: usize,
-    /// Ability to heal wounds.
-    pub chirurgy: usize,
-    /// Ability to repair items.
-    pub craft: usize,
-    /// Ability understand others feelings.
-    pub empathy: usize,
-    /// Ability to find useful items in the wilderness.
-    pub forage: usize,
-    /// Ability to deceive others.
-    pub guile: usize,
-    /// Ability to identify healing ingredients and treat disease/poison.
-    pub heal: usize,
-    /// Ability to track and trap animals.
-    pub hunt: usize,
-    /// Ability to predict events.
-    pub intuition: usize,
-    /// Ability to accurately identify and value dungeon spoils.
-    pub lore: usize,
-
-    // Attributes (competencies)
-    /// Bonus when using bows.
-    pub competence_bow: usize,
-    /// Bonus when using great weapons.
-    pub competence_great: usize,
-    /// Bonus when using shields.
-    pub competence_shield: usize,
-    /// Competence when using single weapons.
-    pub competence_single: usize,
-    /// Bonus when spears.
-    pub competence_spear: usize,
-    /// Bonus when using staves
-    pub competence_staff: usize,
-    /// Bonus when using swords.
-    pub competence_sword: usize,
-    /// Bonus when using warhammers.
-    pub competence_warhammer: usize,
-
-    // Attributes (profession)
-    /// Bonus when acting as a professional adventurer.
-    pub profession_adventurer: usize,
-    /// Bonus when acting as a professional animal handler.
-    pub profession_animalhandler: usize,
-    /// Bonus when acting as a professional apothecarist.
-    pub profession_apothecarist: usize,
-    /// Bonus when acting as a professional appraiser.
-    pub profession_appraiser: usize,
-    /// Bonus when acting as a professional cutter.
-    pub profession_cutter: usize,
-    /// Bonus when acting as a professional farmer.
-    pub profession_farmer: usize,
-    /// Bonus when acting as a professional innkeeper.
-    pub profession_innkeeper: usize,
-    /// Bonus when acting as a professional laborer.
-    pub profession_laborer: usize,
-    /// Bonus when acting as a professional tinker.
-    pub profession_tinker: usize,
-    /// Bonus when acting as a professional trader.
-    pub profession_trader: usize,
-    /// Bonus when acting as a professional woodsman.
-    pub profession_woodsman: usize,
+    // Attributes
+    /// Ability at tasks.
+    pub attributes: Attributes,
+    /// Practice built up in play towards the next point of each attribute.
+    pub practice: BTreeMap<Attribute, usize>,
+    /// Attribute points gained in play towards each training package.
+    pub progress: BTreeMap<String, usize>,
+
+    // Possessions
+    /// What the mob is carrying.
+    pub inventory: Inventory,
+    /// The number of times the mob has been into the dungeon since arriving in town.
+    pub delves: usize,
+}
+
+/// What a mob is carrying.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Inventory {
+    /// Money.
+    pub gold: usize,
+    /// Things brought back from the dungeon, to be sold.
+    pub loot: usize,
+    /// Food and equipment for trips into the dungeon. One is used up on each trip.
+    pub supplies: usize,
 }
 
 /// Life events.
-#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum LifeEvent {
     Born,
-    Raised { childhood: Childhood },
-    Learned { package: TrainingPackage },
+    Raised { childhood: String },
+    Learned { package: String },
     Onset,
+    /// Born to a parent.
+    BornTo {
+        /// The id of the parent.
+        parent: usize,
+    },
+    /// Got married.
+    Married {
+        /// The id of the spouse.
+        spouse: usize,
+    },
+    /// Had a child.
+    HadChild {
+        /// The id of the child.
+        child: usize,
+    },
+    /// Gained a younger brother or sister.
+    SiblingBorn {
+        /// The id of the sibling.
+        sibling: usize,
+    },
+    /// Retired from adventuring to take up a profession.
+    Retired {
+        /// The name of the training package for the profession.
+        package: String,
+    },
+    /// Gained a training package's worth of experience through practice in play.
+    Practised {
+        /// The name of the package.
+        package: String,
+    },
+    /// Nearly died in the dungeon, but lived to tell the tale.
+    NearDeath,
+    /// Came into a lot of money.
+    GotRich,
+    /// Lost a friend.
+    LostFriend {
+        /// The id of the friend.
+        friend: usize,
+    },
+    /// Was robbed.
+    Robbed,
+    /// Gained or lost a personality trait because of something that happened.
+    Changed {
+        /// The name of the trait.
+        name: String,
+        /// Whether the mob now has it.
+        has: bool,
+    },
+}
+
+/// Something which happens to a mob because of another's turn. These are held back until every mob
+/// has had their turn, as otherwise a mob which hasn't yet had theirs would lose it when they do.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Effect {
+    /// Practise an attribute by the given amount.
+    Practise(Attribute, usize),
+    /// Experience a life event.
+    Experience(LifeEvent),
 }
 
+/// The names of the personality traits, as used in data files.
+pub const PERSONALITY_TRAITS: [&'static str; 5] = ["avaricious",
+                                                   "brave",
+                                                   "envious",
+                                                   "gluttonous",
+                                                   "slothful"];
+
 impl Mobile {
+    /// Whether a name is that of a personality trait.
+    pub fn is_personality_trait(name: &str) -> bool {
+        PERSONALITY_TRAITS.contains(&name)
+    }
+
+    /// Whether the mob has a personality trait, or `None` if there's no trait of that name.
+    pub fn personality_trait(&self, name: &str) -> Option<bool> {
+        match name {
+            "avaricious" => Some(self.is_avaricious),
+            "brave" => Some(self.is_brave),
+            "envious" => Some(self.is_envious),
+            "gluttonous" => Some(self.is_gluttonous),
+            "slothful" => Some(self.is_slothful),
+            _ => None,
+        }
+    }
+
+    /// A personality trait, or `None` if there's no trait of that name.
+    pub fn personality_trait_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "avaricious" => Some(&mut self.is_avaricious),
+            "brave" => Some(&mut self.is_brave),
+            "envious" => Some(&mut self.is_envious),
+            "gluttonous" => Some(&mut self.is_gluttonous),
+            "slothful" => Some(&mut self.is_slothful),
+            _ => None,
+        }
+    }
+
+    /// Whether the mob has taken the named training package.
+    pub fn has_learned(&self, package: &str) -> bool {
+        self.history.iter().any(|&(_, ref event)| match *event {
+            LifeEvent::Raised { ref childhood } => childhood == package,
+            LifeEvent::Learned { package: ref p } => p == package,
+            _ => false,
+        })
+    }
+
+    /// Record a life event at the given age, keeping the history sorted. Events at the same age are
+    /// kept in the order they were recorded.
+    pub fn add_history(&mut self, age: usize, event: LifeEvent) {
+        let i = self.history.iter().take_while(|&&(a, _)| a <= age).count();
+        self.history.insert(i, (age, event));
+    }
+
     /// Do a turn.
-    pub fn step(&mut self,
-                pos: Point,
-                mobs: &mut BTreeMap<Point, Mobile>,
-                maps: &mut Maps,
-                world: &mut World) {
-        // TODO: Possibly pick a new priority task.
+    pub fn step<R: Rng>(&mut self,
+                        rng: &mut R,
+                        pos: Point,
+                        mobs: &mut BTreeMap<Point, Mobile>,
+                        maps: &mut Maps,
+                        world: &mut World) {
+        // Another year older, and perhaps ready to retire or die. A death is felt by the family.
+        if world.is_new_day() && world.day_of_year() == self.birthday {
+            if !self.have_birthday(rng, pos, world) {
+                let _ = mobs.remove(&pos);
+                world.leave_job(maps, self.id);
+                for id in self.acquaintances() {
+                    world.affect(id, Effect::Experience(LifeEvent::LostFriend { friend: self.id }));
+                }
+                return;
+            }
+        }
+
+        // Finish with the priority task, if it's done, and perhaps pick a new one.
+        self.plan(rng, pos, mobs, maps, world);
 
-        // TODO: Adjust desires.
+        // Get hungrier and more tired.
+        self.adjust_desires(world);
+
+        // Clock on or off, and while on duty, mend any damage to the counter once an hour.
+        if let (Some(_), Some(w)) = (self.job, self.workplace) {
+            let on_duty = self.activity(world) == Activity::Work && is_adjacent(pos, w);
+            world.set_on_duty(maps, self.id, on_duty);
+            if on_duty && world.is_new_hour() &&
+               world.statics.at(w).map_or(false, |s| s.is_damaged()) {
+                let _ = world.repair(rng, w, self);
+            }
+        }
 
         // Run the AI.
-        self.ai(pos, mobs, maps, world);
+        self.ai(rng, pos, mobs, maps, world);
+
+        // Moving puts a copy of the mob in its new position, but changes made afterwards (or
+        // without moving at all) need to be copied over.
+        for mob in mobs.values_mut() {
+            if mob.id == self.id {
+                *mob = self.clone();
+            }
+        }
     }
 }
@@ -0,0 +1,116 @@
+//! Experience gained in play. Generation gives a mob their attributes by simulating their life so
+//! far, but they don't stop learning once the game starts: a shopkeeper gets better at bargaining
+//! by bargaining, and an adventurer gets better with a sword by swinging it.
+//!
+//! Practice builds up in an attribute until it's enough for a point. Points gained like this count
+//! towards the training packages of the mob's current stage of life which grant that attribute, and
+//! once a package's worth of points has been gained, it's recorded in their history.
+
+use mobiles::{LifeEvent, Mobile};
+use mobiles::attributes::{ATTRIBUTES, Attribute, Category};
+use mobiles::gen::ADULT_AGE;
+use mobiles::skills::{Outcome, TaskKind};
+use mobiles::training::{Stage, Training};
+
+/// The practice needed to gain a point in an attribute.
+const PRACTICE_PER_POINT: usize = 20;
+
+impl Mobile {
+    /// The stage of life the mob is in, for the purposes of training.
+    pub fn stage(&self) -> Stage {
+        self.stage_at(self.age)
+    }
+
+    /// The stage of life the mob was in (or will be in, if nothing changes) at the given age,
+    /// after childhood.
+    pub fn stage_at(&self, age: usize) -> Stage {
+        if self.onset_age.map_or(false, |onset| age >= onset) {
+            Stage::PostOnset
+        } else if age >= ADULT_AGE {
+            Stage::PreOnset
+        } else {
+            Stage::Youth
+        }
+    }
+
+    /// Practise an attribute. Returns the number of points gained.
+    pub fn practise(&mut self, training: &Training, attribute: Attribute, amount: usize) -> usize {
+        let points = {
+            let practice = self.practice.entry(attribute).or_insert(0);
+            *practice += amount;
+            let points = *practice / PRACTICE_PER_POINT;
+            *practice %= PRACTICE_PER_POINT;
+            points
+        };
+        if points == 0 {
+            return 0;
+        }
+        self.attributes.add(attribute, points);
+
+        // Count the points towards any packages the mob could be taking which grant this attribute.
+        let age = self.age;
+        for package in training.stage(self.stage()) {
+            if !package.applicable(self) || !package.grants.iter().any(|&(a, _)| a == attribute) {
+                continue;
+            }
+            let total: usize = package.grants.iter().map(|&(_, n)| n).sum();
+            let done = {
+                let progress = self.progress.entry(package.name.clone()).or_insert(0);
+                *progress += points;
+                if *progress >= total {
+                    *progress -= total;
+                    true
+                } else {
+                    false
+                }
+            };
+            if done {
+                self.add_history(age, LifeEvent::Practised { package: package.name.clone() });
+            }
+        }
+
+        points
+    }
+
+    /// Practise a task. The attribute used improves, as does the profession if the task was part of
+    /// the mob's work; and how much depends on how it went: a near-miss teaches more than an easy
+    /// success, and a disaster teaches little. Returns the number of points gained.
+    pub fn practise_task(&mut self,
+                         training: &Training,
+                         task: TaskKind,
+                         outcome: Outcome,
+                         on_the_job: bool)
+                         -> usize {
+        let amount = practice_for(outcome);
+        let mut points = self.practise(training, task.attribute(), amount);
+        match task.profession() {
+            Some(profession) if on_the_job => {
+                points += self.practise(training, profession, amount);
+            }
+            _ => (),
+        }
+        points
+    }
+
+    /// Practise the weapon the mob is best with, after a fight which went as given. Returns the
+    /// number of points gained.
+    pub fn practise_competence(&mut self, training: &Training, outcome: Outcome) -> usize {
+        let best = ATTRIBUTES.iter()
+            .filter(|a| a.category() == Category::Competence)
+            .max_by_key(|&&a| self.attributes.get(a))
+            .cloned();
+        match best {
+            Some(competence) => self.practise(training, competence, practice_for(outcome)),
+            None => 0,
+        }
+    }
+}
+
+/// How much practice a task which went as given is worth.
+fn practice_for(outcome: Outcome) -> usize {
+    match outcome {
+        Outcome::CriticalFailure | Outcome::CriticalSuccess => 1,
+        Outcome::Failure => 3,
+        Outcome::Success => 2,
+    }
+}
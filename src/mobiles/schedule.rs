@@ -0,0 +1,176 @@
+//! Daily routines. Each mob has a schedule saying what they should be doing at each hour of the
+//! day: sleeping, eating, working, socialising, or doing as they please. The planner picks tasks to
+//! fit, unless a desire has become too pressing to ignore, in which case the mob drops everything
+//! and follows their desires until it has been dealt with.
+//!
+//! A schedule depends on who the mob is. Everyone sleeps at night and eats three meals a day.
+//! Adults work through the day, with a break for lunch, and spend the evening socialising;
+//! adventurers spend their working day in the dungeon, and children play instead. Those with jobs
+//! work the hours their job needs. The slothful lie in, the gluttonous linger over their meals, and
+//! children go to bed early.
+
+use constants::*;
+use dijkstra_map::MapTag;
+use mobiles::Mobile;
+use mobiles::behaviour::Archetype;
+use types::World;
+
+/// How many extra hours the slothful stay in bed.
+const LIE_IN_HOURS: usize = 2;
+
+/// How much earlier than everyone else children go to bed.
+const BEDTIME_HOURS: usize = 2;
+
+/// The hour lunch starts.
+const LUNCH_HOUR: usize = 12;
+
+/// The weight at which a desire becomes urgent, overriding the schedule.
+const URGENT_DESIRE: f64 = 5.0;
+
+/// How much hungrier a mob gets for each waking hour without a meal.
+const HUNGER_PER_HOUR: f64 = 0.5;
+
+/// How much more tired a mob gets for each waking hour.
+const TIREDNESS_PER_HOUR: f64 = 0.25;
+
+/// How much less tired a mob gets for each hour of sleep.
+const REST_PER_HOUR: f64 = 0.5;
+
+/// How much more an adventurer wants adventure for each waking hour.
+const RESTLESSNESS_PER_HOUR: f64 = 0.1;
+
+/// Things a mob can be scheduled to do.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Activity {
+    /// Sleep, in bed or at home.
+    Sleep,
+    /// Have a meal, at an inn if there is one open.
+    Meal,
+    /// Go to work: their workplace for those with jobs, the dungeon for adventurers.
+    Work,
+    /// Spend time with friends and family.
+    Socialise,
+    /// Nothing in particular.
+    Leisure,
+}
+
+/// A daily routine: an activity for each hour of the day.
+#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Schedule {
+    /// The activity for each hour, starting at midnight. If this is empty, the mob has no routine
+    /// and is always at leisure.
+    pub hours: Vec<Activity>,
+}
+
+impl Schedule {
+    /// The schedule for a mob, based on their archetype and personality.
+    pub fn for_mob(mob: &Mobile) -> Schedule {
+        let archetype = mob.archetype();
+        let wake = if mob.is_slothful { WAKE_HOUR + LIE_IN_HOURS } else { WAKE_HOUR };
+        let bed = if archetype == Archetype::Child {
+            SLEEP_HOUR - BEDTIME_HOURS
+        } else {
+            SLEEP_HOUR
+        };
+        let meal = if mob.is_gluttonous { 2 } else { 1 };
+        let (start, end) = mob.job.map_or((WORK_START_HOUR, WORK_END_HOUR), |job| job.shift());
+        let eats_at_work = mob.job.map_or(false, |job| job.eats_at_work());
+
+        // Later activities take priority over earlier ones, so meals cut into the working day
+        // (unless the mob eats at work), and sleeping in cuts into breakfast.
+        let mut schedule = Schedule { hours: vec![Activity::Leisure; HOURS_PER_DAY] };
+        if archetype != Archetype::Child {
+            schedule.set(start, end, Activity::Work);
+        }
+        schedule.set(end, bed, Activity::Socialise);
+        schedule.set(wake, wake + meal, Activity::Meal);
+        schedule.set(LUNCH_HOUR, LUNCH_HOUR + meal, Activity::Meal);
+        schedule.set(end, end + meal, Activity::Meal);
+        if eats_at_work {
+            schedule.set(start, end, Activity::Work);
+        }
+        schedule.set(bed, HOURS_PER_DAY, Activity::Sleep);
+        schedule.set(0, wake, Activity::Sleep);
+        schedule
+    }
+
+    /// Set the activity for the hours from `start` up to (but not including) `end`.
+    pub fn set(&mut self, start: usize, end: usize, activity: Activity) {
+        for hour in start..end {
+            if hour < self.hours.len() {
+                self.hours[hour] = activity;
+            }
+        }
+    }
+
+    /// The activity for an hour.
+    pub fn activity_at(&self, hour: usize) -> Activity {
+        self.hours.get(hour).cloned().unwrap_or(Activity::Leisure)
+    }
+
+    /// The hour at which the activity going on at the given hour ends. If the mob does the same
+    /// thing all day, this is the same hour.
+    pub fn end_of(&self, hour: usize) -> usize {
+        let activity = self.activity_at(hour);
+        (1..HOURS_PER_DAY)
+            .map(|h| (hour + h) % HOURS_PER_DAY)
+            .find(|&h| self.activity_at(h) != activity)
+            .unwrap_or(hour)
+    }
+}
+
+impl Mobile {
+    /// What the mob should be doing now.
+    pub fn activity(&self, world: &World) -> Activity {
+        self.schedule.activity_at(world.hour())
+    }
+
+    /// The turn at which the mob's current activity ends.
+    pub fn activity_end(&self, world: &World) -> usize {
+        world.next_hour(self.schedule.end_of(world.hour()))
+    }
+
+    /// The desire which has become too pressing to ignore, if there is one. If several have, this
+    /// is the strongest.
+    pub fn urgent_desire(&self) -> Option<MapTag> {
+        self.desires
+            .iter()
+            .filter(|&(_, &w)| w >= URGENT_DESIRE)
+            .fold(None, |best: Option<(MapTag, f64)>, (&tag, &w)| match best {
+                Some((_, bw)) if bw >= w => best,
+                _ => Some((tag, w)),
+            })
+            .map(|(tag, _)| tag)
+    }
+
+    /// Desires change over the day. Every waking hour outside mealtimes the mob gets hungrier (the
+    /// gluttonous twice as fast), and they get more tired. Hunger is only seen off by eating
+    /// somewhere which serves food, such as a staffed inn or a well; so those with nowhere to eat
+    /// will sooner or later be driven to look for somewhere. A night's sleep, wherever it is,
+    /// makes up for a day's tiredness, and sleeping in a bed helps more. Adventurers also grow
+    /// restless, until they next go into the dungeon.
+    pub fn adjust_desires(&mut self, world: &World) {
+        if !world.is_new_hour() {
+            return;
+        }
+
+        let activity = self.activity(world);
+        let hunger = if self.is_gluttonous { 2.0 * HUNGER_PER_HOUR } else { HUNGER_PER_HOUR };
+        let sustenance = self.desires.entry(MapTag::Sustenance).or_insert(0.0);
+        match activity {
+            Activity::Meal | Activity::Sleep => (),
+            _ => *sustenance += hunger,
+        }
+
+        let rest = self.desires.entry(MapTag::Rest).or_insert(0.0);
+        if activity == Activity::Sleep {
+            *rest = (*rest - REST_PER_HOUR).max(0.0);
+        } else {
+            *rest += TIREDNESS_PER_HOUR;
+        }
+
+        if activity != Activity::Sleep && self.archetype() == Archetype::Adventurer {
+            *self.desires.entry(MapTag::Adventure).or_insert(0.0) += RESTLESSNESS_PER_HOUR;
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Skill checks: how well a mob does at a task. Each kind of task uses one attribute, and has a
+//! profession whose practitioners are particularly good at it. The outcome is graded, so that the
+//! system using it can distinguish a narrow success from a triumph, or a fumble from a disaster.
+
+use mobiles::Mobile;
+use mobiles::attributes::Attribute;
+use rand::Rng;
+
+/// How far a roll must beat (or miss) the difficulty by to be a critical success (or failure).
+const CRITICAL_MARGIN: usize = 15;
+
+/// Kinds of tasks.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum TaskKind {
+    /// Identify and value an item.
+    Appraise,
+    /// Strike a deal.
+    Trade,
+    /// Serve customers and keep them happy.
+    Serve,
+    /// Heal a wound.
+    Heal,
+    /// Treat poison or disease.
+    Treat,
+    /// Repair an item.
+    Craft,
+    /// Track and trap animals.
+    Hunt,
+    /// Find useful things in the wilderness.
+    Forage,
+    /// Look after animals.
+    TendAnimals,
+    /// Work the fields.
+    Farm,
+    /// Do heavy work.
+    Labour,
+    /// Avoid a trap or an attack.
+    Dodge,
+    /// Find the way in the dungeon.
+    Delve,
+    /// Deceive someone.
+    Deceive,
+    /// Make a good impression.
+    Persuade,
+}
+
+/// How well a task went.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Outcome {
+    /// Failed badly: something went wrong.
+    CriticalFailure,
+    /// Failed.
+    Failure,
+    /// Succeeded.
+    Success,
+    /// Succeeded with something to spare.
+    CriticalSuccess,
+}
+
+impl TaskKind {
+    /// The attribute used for this task.
+    pub fn attribute(&self) -> Attribute {
+        match *self {
+            TaskKind::Appraise => Attribute::Lore,
+            TaskKind::Trade => Attribute::Bargain,
+            TaskKind::Serve => Attribute::Charm,
+            TaskKind::Heal => Attribute::Chirurgy,
+            TaskKind::Treat => Attribute::Heal,
+            TaskKind::Craft => Attribute::Craft,
+            TaskKind::Hunt => Attribute::Hunt,
+            TaskKind::Forage => Attribute::Forage,
+            TaskKind::TendAnimals => Attribute::Animal,
+            TaskKind::Farm => Attribute::Endurance,
+            TaskKind::Labour => Attribute::Strength,
+            TaskKind::Dodge => Attribute::Agility,
+            TaskKind::Delve => Attribute::Intuition,
+            TaskKind::Deceive => Attribute::Guile,
+            TaskKind::Persuade => Attribute::Charm,
+        }
+    }
+
+    /// The profession whose practitioners are good at this task, if there is one.
+    pub fn profession(&self) -> Option<Attribute> {
+        match *self {
+            TaskKind::Appraise => Some(Attribute::ProfessionAppraiser),
+            TaskKind::Trade => Some(Attribute::ProfessionTrader),
+            TaskKind::Serve => Some(Attribute::ProfessionInnkeeper),
+            TaskKind::Heal => Some(Attribute::ProfessionCutter),
+            TaskKind::Treat => Some(Attribute::ProfessionApothecarist),
+            TaskKind::Craft => Some(Attribute::ProfessionTinker),
+            TaskKind::Hunt | TaskKind::Forage => Some(Attribute::ProfessionWoodsman),
+            TaskKind::TendAnimals => Some(Attribute::ProfessionAnimalHandler),
+            TaskKind::Farm => Some(Attribute::ProfessionFarmer),
+            TaskKind::Labour => Some(Attribute::ProfessionLaborer),
+            TaskKind::Dodge | TaskKind::Delve => Some(Attribute::ProfessionAdventurer),
+            TaskKind::Deceive | TaskKind::Persuade => None,
+        }
+    }
+}
+
+impl Outcome {
+    /// Whether this is a success of some sort.
+    pub fn is_success(&self) -> bool {
+        *self >= Outcome::Success
+    }
+
+    /// Whether this is critical, one way or the other.
+    pub fn is_critical(&self) -> bool {
+        *self == Outcome::CriticalFailure || *self == Outcome::CriticalSuccess
+    }
+}
+
+impl Mobile {
+    /// Attempt a task of the given difficulty. The roll is the relevant attribute, plus a die roll,
+    /// plus a bonus for the matching profession if the task is part of the mob's work; and how far
+    /// it is from the difficulty determines the outcome.
+    pub fn attempt<R: Rng>(&self,
+                           rng: &mut R,
+                           task: TaskKind,
+                           difficulty: usize,
+                           on_the_job: bool)
+                           -> Outcome {
+        let profession = if on_the_job { task.profession() } else { None };
+        let roll = self.attributes.roll(rng, task.attribute(), profession);
+        if roll >= difficulty + CRITICAL_MARGIN {
+            Outcome::CriticalSuccess
+        } else if roll >= difficulty {
+            Outcome::Success
+        } else if roll + CRITICAL_MARGIN > difficulty {
+            Outcome::Failure
+        } else {
+            Outcome::CriticalFailure
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! Tasks: things a mob sets out to do, which take more than one turn. A task has conditions for
+//! when it is done and when it has failed, and (unless it is one of the primitive tasks, which the
+//! AI can do directly) is made up of subtasks, which are worked through in order. For example,
+//! selling loot is made up of approaching a store and then selling.
+//!
+//! Each turn, a mob with a priority task does the first subtask which isn't done yet. If the task
+//! (or any of its subtasks) fails, it is given up. Once it is done or given up, the planner picks
+//! another, based on the mob's schedule, their desires, and what they are carrying.
+
+use constants::*;
+use dijkstra_map::{MapTag, Maps};
+use grid::Point;
+use mobiles::{LifeEvent, Mobile};
+use mobiles::schedule::Activity;
+use mobiles::skills::{Outcome, TaskKind};
+use rand::Rng;
+use std::collections::BTreeMap;
+use types::{Message, World};
+use utils::is_adjacent;
+
+/// Specific tasks that an AI can perform.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Task {
+    // Primitive tasks.
+    /// Move to the given location.
+    MoveTo(Point),
+    /// Move next to (or onto) the given location.
+    Reach(Point),
+    /// Move next to (or onto) the nearest source of a heatmap.
+    Approach(MapTag),
+    /// Stay put until the given turn.
+    Wait(usize),
+    /// Buy a day's supplies at an adjacent store.
+    Buy,
+    /// Sell all loot at an adjacent store.
+    Sell,
+    /// Go into the dungeon through an adjacent entrance, and come back out.
+    Delve,
+    /// Run away from a point until the given turn.
+    Flee(Point, usize),
+
+    // Compound tasks.
+    /// Go home.
+    GoHome,
+    /// Sleep, in a bed if there is one and otherwise at home, until the given turn.
+    Sleep(usize),
+    /// Eat, at an inn if there is one open, until the given turn. With nowhere to eat, the mob waits
+    /// at home, and goes hungry.
+    Eat(usize),
+    /// Spend time at an inn if there is one, and otherwise at home, until the given turn.
+    Socialise(usize),
+    /// Buy supplies. This is done once the mob has more than the given amount.
+    BuyItem(usize),
+    /// Sell all loot.
+    SellLoot,
+    /// Go to work until the given turn.
+    WorkShift(usize),
+    /// Go and see the mob with the given id.
+    VisitFriend(usize),
+    /// Go into the dungeon. This is done once the mob has made more than the given number of trips.
+    EnterDungeon(usize),
+}
+
+/// How a task is going.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Status {
+    /// Still being worked on.
+    InProgress,
+    /// Finished successfully.
+    Done,
+    /// Can't be finished.
+    Failed,
+}
+
+impl Task {
+    /// Check how the task is going for a mob at the given position.
+    pub fn status(&self,
+                  mob: &Mobile,
+                  pos: Point,
+                  mobs: &BTreeMap<Point, Mobile>,
+                  maps: &Maps,
+                  world: &World)
+                  -> Status {
+        let done_if = |done: bool| if done { Status::Done } else { Status::InProgress };
+        match *self {
+            Task::MoveTo(target) => done_if(pos == target),
+            Task::Reach(target) => done_if(is_adjacent(pos, target)),
+            Task::Approach(tag) => {
+                let map = maps.get(tag);
+                if map.sources.is_empty() {
+                    Status::Failed
+                } else {
+                    done_if(adjacent_points(pos).iter().any(|&p| map.approach.at(p) == 0.0))
+                }
+            }
+            Task::Wait(until) | Task::Flee(_, until) | Task::Sleep(until) | Task::Eat(until) |
+            Task::Socialise(until) => done_if(world.turn >= until),
+            Task::Buy | Task::Sell | Task::Delve => Status::InProgress,
+            Task::GoHome => done_if(pos == mob.home_pos),
+            Task::BuyItem(had) => {
+                if mob.inventory.supplies > had {
+                    Status::Done
+                } else if mob.inventory.gold < SUPPLY_PRICE {
+                    Status::Failed
+                } else {
+                    Status::InProgress
+                }
+            }
+            Task::SellLoot => done_if(mob.inventory.loot == 0),
+            Task::WorkShift(until) => {
+                if mob.workplace.is_none() {
+                    Status::Failed
+                } else {
+                    done_if(world.turn >= until)
+                }
+            }
+            Task::VisitFriend(id) => {
+                match mobs.iter().find(|&(_, m)| m.id == id) {
+                    Some((&friend_pos, _)) => done_if(is_adjacent(pos, friend_pos)),
+                    None => Status::Failed,
+                }
+            }
+            Task::EnterDungeon(trips) => {
+                if mob.delves > trips {
+                    Status::Done
+                } else if mob.inventory.supplies == 0 {
+                    Status::Failed
+                } else {
+                    Status::InProgress
+                }
+            }
+        }
+    }
+
+    /// The subtasks making up the task, in order. Primitive tasks have none.
+    pub fn subtasks(&self,
+                    mob: &Mobile,
+                    mobs: &BTreeMap<Point, Mobile>,
+                    maps: &Maps)
+                    -> Vec<Task> {
+        match *self {
+            Task::MoveTo(_) | Task::Reach(_) | Task::Approach(_) | Task::Wait(_) | Task::Buy |
+            Task::Sell | Task::Delve | Task::Flee(_, _) => Vec::new(),
+            Task::GoHome => vec![Task::MoveTo(mob.home_pos)],
+            Task::Sleep(until) => stay_at(MapTag::Rest, mob, maps, until),
+            Task::Eat(until) |
+            Task::Socialise(until) => stay_at(MapTag::Sustenance, mob, maps, until),
+            Task::BuyItem(_) => vec![Task::Approach(MapTag::GeneralStore), Task::Buy],
+            Task::SellLoot => vec![Task::Approach(MapTag::GeneralStore), Task::Sell],
+            Task::WorkShift(until) => {
+                match mob.workplace {
+                    Some(workplace) => vec![Task::Reach(workplace), Task::Wait(until)],
+                    None => Vec::new(),
+                }
+            }
+            Task::VisitFriend(id) => {
+                match mobs.iter().find(|&(_, m)| m.id == id) {
+                    Some((&friend_pos, _)) => vec![Task::Reach(friend_pos)],
+                    None => Vec::new(),
+                }
+            }
+            Task::EnterDungeon(_) => vec![Task::Approach(MapTag::Adventure), Task::Delve],
+        }
+    }
+
+    /// Whether this is a primitive task, which the AI can do directly.
+    pub fn is_primitive(&self) -> bool {
+        match *self {
+            Task::MoveTo(_) | Task::Reach(_) | Task::Approach(_) | Task::Wait(_) | Task::Buy |
+            Task::Sell | Task::Delve | Task::Flee(_, _) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Mobile {
+    /// Find the primitive task to work on next for the given task: the task itself if it is
+    /// primitive, and otherwise the first subtask which isn't done yet (recursively). Returns
+    /// `None` if the task is done or has failed.
+    pub fn next_step(&self,
+                     task: Task,
+                     pos: Point,
+                     mobs: &BTreeMap<Point, Mobile>,
+                     maps: &Maps,
+                     world: &World)
+                     -> Option<Task> {
+        if task.status(self, pos, mobs, maps, world) != Status::InProgress {
+            return None;
+        }
+        if task.is_primitive() {
+            return Some(task);
+        }
+        for subtask in task.subtasks(self, mobs, maps) {
+            match subtask.status(self, pos, mobs, maps, world) {
+                Status::Done => continue,
+                Status::Failed => return None,
+                Status::InProgress => return self.next_step(subtask, pos, mobs, maps, world),
+            }
+        }
+        None
+    }
+
+    /// Make progress on the priority task. Returns `false` if no progress could be made.
+    pub fn ai_do_task<R: Rng>(&mut self,
+                              rng: &mut R,
+                              pos: Point,
+                              mobs: &mut BTreeMap<Point, Mobile>,
+                              maps: &mut Maps,
+                              world: &mut World)
+                              -> bool {
+        let step = match self.priority_task {
+            Some(task) => self.next_step(task, pos, mobs, maps, world),
+            None => None,
+        };
+        let target = match step {
+            Some(Task::MoveTo(p)) | Some(Task::Reach(p)) => self.ai_pathfind(pos, world, p),
+            Some(Task::Approach(tag)) => self.ai_approach(pos, maps, tag),
+            Some(Task::Flee(from, _)) => self.ai_flee(pos, world, from),
+            Some(Task::Wait(_)) => return true,
+            Some(Task::Buy) => return self.ai_buy_commit(world),
+            Some(Task::Sell) => return self.ai_sell_commit(rng, world),
+            Some(Task::Delve) => return self.ai_delve_commit(rng, pos, maps, world),
+            _ => None,
+        };
+        match target {
+            Some(new_pos) => self.ai_move_commit(pos, mobs, world, new_pos),
+            None => false,
+        }
+    }
+
+    /// Buy a day's supplies.
+    fn ai_buy_commit(&mut self, world: &mut World) -> bool {
+        if self.inventory.gold < SUPPLY_PRICE {
+            return false;
+        }
+        self.inventory.gold -= SUPPLY_PRICE;
+        self.inventory.supplies += 1;
+        world.tax(1);
+        true
+    }
+
+    /// Sell all loot. How much it goes for depends on how well the mob haggles, and a good enough
+    /// haul can make them rich.
+    fn ai_sell_commit<R: Rng>(&mut self, rng: &mut R, world: &mut World) -> bool {
+        if self.inventory.loot == 0 {
+            return false;
+        }
+        let outcome = self.attempt(rng, TaskKind::Trade, LOOT_PRICE, false);
+        let price = match outcome {
+            Outcome::CriticalSuccess => LOOT_PRICE * 2,
+            Outcome::Success => LOOT_PRICE,
+            Outcome::Failure => LOOT_PRICE / 2,
+            Outcome::CriticalFailure => LOOT_PRICE / 4,
+        };
+        let was_rich = self.inventory.gold >= RICH_GOLD;
+        self.inventory.gold += price * self.inventory.loot;
+        self.inventory.loot = 0;
+        let _ = self.practise_task(&world.training, TaskKind::Trade, outcome, false);
+        if !was_rich && self.inventory.gold >= RICH_GOLD {
+            let _ = self.experience(rng, LifeEvent::GotRich);
+        }
+        true
+    }
+
+    /// Go into the dungeon and come back out, using up a day's supplies. The better they do, the
+    /// more loot they bring back; but a bad trip is a brush with death, and they run for it.
+    fn ai_delve_commit<R: Rng>(&mut self,
+                               rng: &mut R,
+                               pos: Point,
+                               maps: &Maps,
+                               world: &mut World)
+                               -> bool {
+        if self.inventory.supplies == 0 {
+            return false;
+        }
+        self.inventory.supplies -= 1;
+        self.delves += 1;
+        if let Some(desire) = self.desires.get_mut(&MapTag::Adventure) {
+            *desire = (*desire - 1.0).max(0.0);
+        }
+
+        let outcome = self.attempt(rng, TaskKind::Delve, DELVE_DIFFICULTY, true);
+        let _ = self.practise_task(&world.training, TaskKind::Delve, outcome, true);
+        let _ = self.practise_competence(&world.training, outcome);
+        match outcome {
+            Outcome::CriticalSuccess => self.inventory.loot += 3,
+            Outcome::Success => self.inventory.loot += 1,
+            Outcome::Failure => (),
+            Outcome::CriticalFailure => {
+                let _ = self.experience(rng, LifeEvent::NearDeath);
+                let heal = self.attempt(rng, TaskKind::Heal, HEAL_DIFFICULTY, false);
+                let _ = self.practise_task(&world.training, TaskKind::Heal, heal, false);
+                world.log(Message {
+                    msg: format!("{} barely escaped the dungeon with their life!", self.name),
+                    loc: Some(pos),
+                });
+                let entrance = adjacent_points(pos)
+                    .into_iter()
+                    .find(|&p| maps.get(MapTag::Adventure).approach.at(p) == 0.0)
+                    .unwrap_or(pos);
+                self.priority_task = Some(Task::Flee(entrance, world.turn + FLEE_TURNS));
+            }
+        }
+        true
+    }
+
+    /// Drop the priority task if it is done, has failed, or has been overridden by an urgent
+    /// desire; and if there isn't one, pick one. Unless a desire is urgent, this follows the mob's
+    /// schedule:
+    ///
+    /// - When it's time to sleep or eat, they go to bed or have a meal.
+    /// - At work, those with jobs go to their workplace. Adventurers sell any loot, buy supplies if
+    ///   they need to, and go into the dungeon.
+    /// - In the evening, they sell any loot and then visit a friend or relative if any are about,
+    ///   before spending the rest of the evening at the inn.
+    /// - At leisure, they sell any loot.
+    ///
+    /// Otherwise, they have no priority task, and just follow their desires. Running away from
+    /// danger can't be overridden.
+    pub fn plan<R: Rng>(&mut self,
+                        rng: &mut R,
+                        pos: Point,
+                        mobs: &BTreeMap<Point, Mobile>,
+                        maps: &Maps,
+                        world: &World) {
+        if let Some(task) = self.priority_task {
+            let overridden = match task {
+                Task::Flee(_, _) => false,
+                _ => self.urgent_desire().is_some(),
+            };
+            if !overridden && self.next_step(task, pos, mobs, maps, world).is_some() {
+                return;
+            }
+        }
+        self.priority_task = self.choose_task(rng, pos, mobs, maps, world);
+    }
+
+    /// Pick a new priority task, if there is anything worth doing.
+    fn choose_task<R: Rng>(&self,
+                           rng: &mut R,
+                           pos: Point,
+                           mobs: &BTreeMap<Point, Mobile>,
+                           maps: &Maps,
+                           world: &World)
+                           -> Option<Task> {
+        if self.urgent_desire().is_some() {
+            return None;
+        }
+
+        let until = self.activity_end(world);
+        let is_adventurer = self.onset_age.is_some() && self.retirement_age.is_none();
+        let mut candidates = Vec::new();
+        match self.activity(world) {
+            Activity::Sleep => candidates.push(Task::Sleep(until)),
+            Activity::Meal => candidates.push(Task::Eat(until)),
+            Activity::Work if is_adventurer => {
+                candidates.push(Task::SellLoot);
+                if self.inventory.supplies == 0 {
+                    candidates.push(Task::BuyItem(self.inventory.supplies));
+                }
+                candidates.push(Task::EnterDungeon(self.delves));
+            }
+            Activity::Work => candidates.push(Task::WorkShift(until)),
+            Activity::Socialise => {
+                candidates.push(Task::SellLoot);
+                let friends: Vec<usize> = self.acquaintances()
+                    .into_iter()
+                    .filter(|&id| mobs.values().any(|m| m.id == id))
+                    .collect();
+                if let Some(&id) = rng.choose(&friends) {
+                    candidates.push(Task::VisitFriend(id));
+                }
+                candidates.push(Task::Socialise(until));
+            }
+            Activity::Leisure => candidates.push(Task::SellLoot),
+        }
+
+        candidates.into_iter().find(|&task| self.next_step(task, pos, mobs, maps, world).is_some())
+    }
+
+    /// The ids of the mobs this one knows: their family.
+    pub fn acquaintances(&self) -> Vec<usize> {
+        self.history
+            .iter()
+            .filter_map(|&(_, ref event)| match *event {
+                LifeEvent::BornTo { parent: id } |
+                LifeEvent::Married { spouse: id } |
+                LifeEvent::HadChild { child: id } |
+                LifeEvent::SiblingBorn { sibling: id } => Some(id),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Go to the nearest source of a heatmap if there is one, and otherwise home, and stay there until
+/// the given turn.
+fn stay_at(tag: MapTag, mob: &Mobile, maps: &Maps, until: usize) -> Vec<Task> {
+    if !maps.get(tag).sources.is_empty() {
+        vec![Task::Approach(tag), Task::Wait(until)]
+    } else {
+        vec![Task::MoveTo(mob.home_pos), Task::Wait(until)]
+    }
+}
+
+/// A point and its neighbours.
+fn adjacent_points(pos: Point) -> Vec<Point> {
+    let mut points = Vec::new();
+    for y in pos.y.saturating_sub(1)..pos.y + 2 {
+        for x in pos.x.saturating_sub(1)..pos.x + 2 {
+            if x < WIDTH && y < HEIGHT {
+                points.push(Point { x: x, y: y });
+            }
+        }
+    }
+    points
+}
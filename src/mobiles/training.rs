@@ -0,0 +1,281 @@
+//! Training packages, which are used by the mob generator to simulate a life. They are loaded from
+//! a data file (`training.txt`, by default), so that new packages can be added, and existing ones
+//! rebalanced, without recompiling; the game also has a copy built in, for when there is no such
+//! file. The format is described at the top of that file.
+//!
+//! A package has a duration, the stages of life in which it can be taken, the attributes it grants,
+//! prerequisites, and relations to other packages. Relations are used to bias the generator towards
+//! a sensible career: someone isn't going to be a farmer one year and a conman the next.
+
+use mobiles::Mobile;
+use mobiles::attributes::Attribute;
+use std::fs::File;
+use std::io::Read;
+
+/// The length of a childhood package, which is also the minimum age of a mob.
+pub const CHILDHOOD_YEARS: usize = 5;
+
+/// The stages of life in which a training package can be taken.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Stage {
+    /// Early childhood. Every mob gets exactly one of these.
+    Childhood,
+    /// From the end of childhood until adulthood.
+    Youth,
+    /// Adulthood, before becoming an adventurer (if they ever do).
+    PreOnset,
+    /// After becoming an adventurer.
+    PostOnset,
+}
+
+/// A training package: a number of years spent in some role, such as a scout or a farmer.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct TrainingPackage {
+    /// The name, used to refer to the package from elsewhere.
+    pub name: String,
+    /// The number of years the package takes.
+    pub years: usize,
+    /// The stages of life the package can be taken in.
+    pub stages: Vec<Stage>,
+    /// The attributes increased by the package, and by how much.
+    pub grants: Vec<(Attribute, usize)>,
+    /// Personality traits and packages, one of which a mob must have to take this. If empty,
+    /// anyone can.
+    pub requires: Vec<String>,
+    /// Packages which are related to this one. This is symmetric: if a package lists another as
+    /// related, each is in the other's list.
+    pub related: Vec<String>,
+    /// How the package reads in a biography, if given.
+    pub prose: Option<String>,
+}
+
+/// All the training packages.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Training {
+    /// The packages, in the order they were loaded.
+    packages: Vec<TrainingPackage>,
+}
+
+impl Training {
+    /// Load training packages from a file.
+    pub fn load(path: &str) -> Result<Training, String> {
+        let mut input = String::new();
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut input)) {
+            Ok(_) => Training::parse(&input),
+            Err(err) => Err(format!("Could not read {:?}: {}", path, err)),
+        }
+    }
+
+    /// Parse training packages.
+    pub fn parse(input: &str) -> Result<Training, String> {
+        let mut packages: Vec<TrainingPackage> = Vec::new();
+        for (n, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |msg: &str| format!("Line {}: {}", n + 1, msg);
+
+            // A new package.
+            if line.starts_with('[') && line.ends_with(']') {
+                packages.push(TrainingPackage {
+                    name: line[1..line.len() - 1].trim().to_string(),
+                    years: 0,
+                    stages: Vec::new(),
+                    grants: Vec::new(),
+                    requires: Vec::new(),
+                    related: Vec::new(),
+                    prose: None,
+                });
+                continue;
+            }
+
+            // A property of the current package.
+            let package = try!(packages.last_mut().ok_or(err("Property outside of a package.")));
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match (words[0], words.len()) {
+                ("years", 2) => {
+                    package.years = try!(words[1].parse().map_err(|_| err("Bad number of years.")))
+                }
+                ("stage", _) => {
+                    for word in &words[1..] {
+                        package.stages.push(match *word {
+                            "childhood" => Stage::Childhood,
+                            "youth" => Stage::Youth,
+                            "pre-onset" => Stage::PreOnset,
+                            "post-onset" => Stage::PostOnset,
+                            _ => return Err(err("Unknown stage.")),
+                        });
+                    }
+                }
+                ("grant", 3) => {
+                    let attribute = try!(Attribute::from_name(words[1])
+                        .ok_or(err("Unknown attribute.")));
+                    let amount = try!(words[2].parse().map_err(|_| err("Bad grant.")));
+                    package.grants.push((attribute, amount));
+                }
+                ("requires", _) => {
+                    package.requires.extend(words[1..].iter().map(|w| w.to_string()))
+                }
+                ("related", _) => package.related.extend(words[1..].iter().map(|w| w.to_string())),
+                ("prose", n) if n > 1 => package.prose = Some(line[5..].trim().to_string()),
+                _ => return Err(err("Unknown property.")),
+            }
+        }
+
+        let mut training = Training { packages: packages };
+        try!(training.check());
+        Ok(training)
+    }
+
+    /// Look up a package by name.
+    pub fn get(&self, name: &str) -> Option<&TrainingPackage> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+
+    /// All the packages which can be taken in a stage of life.
+    pub fn stage(&self, stage: Stage) -> Vec<&TrainingPackage> {
+        self.packages.iter().filter(|p| p.stages.contains(&stage)).collect()
+    }
+
+    /// Check that the packages are consistent, and make the relations symmetric.
+    fn check(&mut self) -> Result<(), String> {
+        let names: Vec<String> = self.packages.iter().map(|p| p.name.clone()).collect();
+        for package in &self.packages {
+            let err = |msg: &str| Err(format!("Package {:?}: {}", package.name, msg));
+            if names.iter().filter(|n| **n == package.name).count() > 1 {
+                return err("Defined more than once.");
+            }
+            if package.years == 0 {
+                return err("Takes no time.");
+            }
+            if package.stages.is_empty() {
+                return err("Not in any stage.");
+            }
+            if package.is_childhood() && package.stages.len() > 1 {
+                return err("Childhoods can't be taken in any other stage.");
+            }
+            if package.is_childhood() && package.years != CHILDHOOD_YEARS {
+                return err("Childhoods must last exactly CHILDHOOD_YEARS.");
+            }
+            for name in &package.requires {
+                if !Mobile::is_personality_trait(name) && !names.contains(name) {
+                    return err("Requires an unknown trait or package.");
+                }
+            }
+            for name in &package.related {
+                if !names.contains(name) {
+                    return err("Related to an unknown package.");
+                }
+            }
+        }
+
+        // Every stage needs something to take; and all but childhood need a one-year package
+        // anyone can take, so any number of years can be filled.
+        for &stage in &[Stage::Childhood, Stage::Youth, Stage::PreOnset, Stage::PostOnset] {
+            let packages = self.stage(stage);
+            if packages.is_empty() {
+                return Err(format!("No packages for {:?}.", stage));
+            }
+            if stage != Stage::Childhood &&
+               !packages.iter().any(|p| p.years == 1 && p.requires.is_empty()) {
+                return Err(format!("No one-year package without requirements for {:?}.", stage));
+            }
+        }
+
+        // Make the relations symmetric.
+        for i in 0..self.packages.len() {
+            for j in 0..self.packages.len() {
+                let name = self.packages[i].name.clone();
+                if self.packages[j].related.contains(&name) &&
+                   !self.packages[i].related.contains(&self.packages[j].name) {
+                    let other = self.packages[j].name.clone();
+                    self.packages[i].related.push(other);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TrainingPackage {
+    /// Whether the given package is somewhat related to this one. This is a reflexive and symmetric
+    /// relation, but not necessarily transitive.
+    pub fn related(&self, other: &TrainingPackage) -> bool {
+        self.name == other.name || self.related.contains(&other.name)
+    }
+
+    /// Whether the given mob is capable of using this training package. Life stage conditions are
+    /// imposed by the caller, who picks which stage to take packages from.
+    pub fn applicable(&self, mob: &Mobile) -> bool {
+        self.requires.is_empty() ||
+        self.requires.iter().any(|r| {
+            mob.personality_trait(r).unwrap_or_else(|| mob.has_learned(r))
+        })
+    }
+
+    /// How the package reads in a biography: see the `prose` field. If none was given, the name is
+    /// used.
+    pub fn prose(&self) -> String {
+        match self.prose {
+            Some(ref prose) => prose.clone(),
+            None if self.is_childhood() => format!("a {} childhood", self.name.to_lowercase()),
+            None => format!("training as {}", self.name.to_lowercase()),
+        }
+    }
+
+    /// Whether this is a childhood.
+    pub fn is_childhood(&self) -> bool {
+        self.stages.contains(&Stage::Childhood)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Training;
+
+    /// The smallest set of packages which passes the checks.
+    const MINIMAL: &'static str = "
+[Kid]
+years 5
+stage childhood
+
+[Odd jobs]
+years 1
+stage youth pre-onset post-onset
+";
+
+    #[test]
+    fn parse_minimal() {
+        let training = Training::parse(MINIMAL).unwrap();
+        assert!(training.get("Kid").is_some());
+        assert!(training.get("Odd jobs").is_some());
+    }
+
+    #[test]
+    fn parse_unknown_property() {
+        let input = format!("{}colour blue\n", MINIMAL);
+        assert!(Training::parse(&input).unwrap_err().contains("Unknown property"));
+    }
+
+    #[test]
+    fn check_duplicate_package() {
+        let input = format!("{}\n[Kid]\nyears 5\nstage childhood\n", MINIMAL);
+        assert!(Training::parse(&input).unwrap_err().contains("Defined more than once"));
+    }
+
+    #[test]
+    fn check_unknown_relation() {
+        let input = format!("{}related Nonsense\n", MINIMAL);
+        assert!(Training::parse(&input).unwrap_err().contains("Related to an unknown package"));
+    }
+
+    #[test]
+    fn check_relations_made_symmetric() {
+        let input = format!("{}related Kid\n", MINIMAL);
+        let training = Training::parse(&input).unwrap();
+        assert_eq!(training.get("Odd jobs").unwrap().related, vec!["Kid".to_string()]);
+        assert_eq!(training.get("Kid").unwrap().related, vec!["Odd jobs".to_string()]);
+    }
+}
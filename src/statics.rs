@@ -2,6 +2,10 @@
 //! all fit into this category.
 
 use dijkstra_map::*;
+use std::cmp;
+
+/// The amount of wear which costs a static one hit point.
+const WEAR_PER_HP: usize = 10;
 
 /// Things which have a fixed presence in the world.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -12,6 +16,17 @@ pub struct Static {
     pub is_impassable: bool,
     /// Acts as an obstruction to line-of-sight.
     pub is_opaque: bool,
+    /// The state of the door, if this is one.
+    pub door: Option<DoorState>,
+    /// How much more damage this can take before it is destroyed.
+    pub hp: usize,
+    /// The hit points of this when in perfect condition.
+    pub max_hp: usize,
+    /// Accumulated wear from use. Every `WEAR_PER_HP` costs a hit point.
+    pub wear: usize,
+    /// The id of the mob which owns this, if it is privately owned, as a counter is by the worker
+    /// behind it. Otherwise it belongs to the town.
+    pub owner: Option<usize>,
 }
 
 /// Types of `Static`s.
@@ -27,25 +42,79 @@ pub enum StaticTag {
     Wall,
     /// A bed: source of Rest, passable, not opaque.
     Bed,
-    /// A door: passable, not opaque.
+    /// A door: passable unless locked, opaque unless open.
     Door,
+    /// A well: source of Sustenance, impassable, not opaque.
+    Well,
+    /// A shrine: impassable, not opaque.
+    Shrine,
+    /// The anvil of a smithy: impassable, not opaque.
+    Anvil,
+    /// A stall in a stable: passable, not opaque.
+    Stable,
+    /// A market stall: source of GeneralStore, impassable, not opaque.
+    MarketStall,
+    /// A road: passable, not opaque.
+    Road,
+
+    // Natural terrain
+    /// Grass: passable, not opaque.
+    Grass,
+    /// A tree: impassable, opaque.
+    Tree,
+    /// Bare rock: impassable, opaque.
+    Rock,
+    /// Shallow water, which can be waded through: passable, not opaque.
+    ShallowWater,
+    /// Deep water: impassable, not opaque.
+    DeepWater,
+}
+
+/// The states a door can be in.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DoorState {
+    /// Passable, not opaque.
+    Open,
+    /// Passable, opaque: mobs can open it as they go through, but can't see through it.
+    Closed,
+    /// Impassable, opaque.
+    Locked,
 }
 
 impl Static {
     /// Construct a new `Static` from its tag.
     pub fn new(tag: StaticTag) -> Static {
-        Static {
+        let max_hp = match tag {
+            StaticTag::Dungeon => usize::max_value(),
+            StaticTag::Wall => 100,
+            StaticTag::Door => 50,
+            StaticTag::GStoreCounter | StaticTag::InnCounter => 40,
+            StaticTag::Bed => 20,
+            StaticTag::Well | StaticTag::Shrine | StaticTag::Anvil => 80,
+            StaticTag::Stable | StaticTag::MarketStall => 30,
+            StaticTag::Road => 200,
+            StaticTag::Grass | StaticTag::ShallowWater | StaticTag::DeepWater => {
+                usize::max_value()
+            }
+            StaticTag::Tree => 60,
+            StaticTag::Rock => 300,
+        };
+
+        let mut s = Static {
             tag: tag,
-            is_impassable: match tag {
-                StaticTag::Dungeon | StaticTag::GStoreCounter | StaticTag::InnCounter |
-                StaticTag::Wall => true,
-                _ => false,
-            },
-            is_opaque: match tag {
-                StaticTag::Wall => true,
-                _ => false,
+            is_impassable: false,
+            is_opaque: false,
+            door: match tag {
+                StaticTag::Door => Some(DoorState::Closed),
+                _ => None,
             },
-        }
+            hp: max_hp,
+            max_hp: max_hp,
+            wear: 0,
+            owner: None,
+        };
+        s.update_flags();
+        s
     }
 
     /// The `MapTag` that this contributes to.
@@ -55,7 +124,116 @@ impl Static {
             StaticTag::GStoreCounter => Some(MapTag::GeneralStore),
             StaticTag::InnCounter => Some(MapTag::Sustenance),
             StaticTag::Bed => Some(MapTag::Rest),
-            StaticTag::Wall | StaticTag::Door => None,
+            StaticTag::Well => Some(MapTag::Sustenance),
+            StaticTag::MarketStall => Some(MapTag::GeneralStore),
+            StaticTag::Wall | StaticTag::Door | StaticTag::Shrine | StaticTag::Anvil |
+            StaticTag::Stable | StaticTag::Road | StaticTag::Grass | StaticTag::Tree |
+            StaticTag::Rock | StaticTag::ShallowWater | StaticTag::DeepWater => None,
+        }
+    }
+
+    /// Whether this only works while someone is working at it. It's recorded as a source in the
+    /// world, but only added to its heatmap while someone is on duty; so mobs won't be drawn to it,
+    /// or get anything out of it, if there's nobody there.
+    pub fn needs_staff(&self) -> bool {
+        match self.tag {
+            StaticTag::InnCounter => true,
+            _ => false,
+        }
+    }
+
+    /// Whether this is part of the natural terrain, rather than something which was built.
+    pub fn is_natural(&self) -> bool {
+        match self.tag {
+            StaticTag::Grass | StaticTag::Tree | StaticTag::Rock | StaticTag::ShallowWater |
+            StaticTag::DeepWater => true,
+            _ => false,
+        }
+    }
+
+    /// How much gold it costs to build this.
+    pub fn cost(&self) -> usize {
+        match self.tag {
+            StaticTag::Dungeon => 0,
+            StaticTag::GStoreCounter | StaticTag::InnCounter => 10,
+            StaticTag::Wall => 2,
+            StaticTag::Bed => 8,
+            StaticTag::Door => 5,
+            StaticTag::Well => 30,
+            StaticTag::Shrine => 40,
+            StaticTag::Anvil => 25,
+            StaticTag::Stable | StaticTag::MarketStall => 15,
+            StaticTag::Road => 1,
+            StaticTag::Grass | StaticTag::Tree | StaticTag::Rock | StaticTag::ShallowWater |
+            StaticTag::DeepWater => 0,
+        }
+    }
+
+    /// Change the state of a door. Returns `false` if this isn't a door.
+    pub fn set_door(&mut self, state: DoorState) -> bool {
+        if self.door.is_none() {
+            return false;
+        }
+        self.door = Some(state);
+        self.update_flags();
+        true
+    }
+
+    /// Use this, wearing it down a little. Returns `false` if this destroys it.
+    pub fn use_once(&mut self) -> bool {
+        self.wear += 1;
+        if self.wear >= WEAR_PER_HP {
+            self.wear -= WEAR_PER_HP;
+            self.damage(1)
+        } else {
+            true
         }
     }
+
+    /// Damage this. Returns `false` if this destroys it.
+    pub fn damage(&mut self, amount: usize) -> bool {
+        self.hp = self.hp.saturating_sub(amount);
+        self.hp > 0
+    }
+
+    /// Repair this, with the given `craft` attribute. A better craftsman restores more hit points,
+    /// and also undoes some of the wear. Returns the number of hit points restored.
+    pub fn repair(&mut self, craft: usize) -> usize {
+        let restored = cmp::min(self.max_hp - self.hp, 1 + craft / 5);
+        self.hp += restored;
+        self.wear = self.wear.saturating_sub(craft / 10);
+        restored
+    }
+
+    /// Whether this has taken any damage.
+    pub fn is_damaged(&self) -> bool {
+        self.hp < self.max_hp
+    }
+
+    /// Recompute the passability and opacity from the tag and door state.
+    fn update_flags(&mut self) {
+        self.is_impassable = match (self.tag, self.door) {
+            (_, Some(DoorState::Locked)) => true,
+            (StaticTag::Dungeon, _) |
+            (StaticTag::GStoreCounter, _) |
+            (StaticTag::InnCounter, _) |
+            (StaticTag::Wall, _) |
+            (StaticTag::Well, _) |
+            (StaticTag::Shrine, _) |
+            (StaticTag::Anvil, _) |
+            (StaticTag::MarketStall, _) |
+            (StaticTag::Tree, _) |
+            (StaticTag::Rock, _) |
+            (StaticTag::DeepWater, _) => true,
+            _ => false,
+        };
+        self.is_opaque = match (self.tag, self.door) {
+            (_, Some(DoorState::Closed)) |
+            (_, Some(DoorState::Locked)) => true,
+            (StaticTag::Wall, _) |
+            (StaticTag::Tree, _) |
+            (StaticTag::Rock, _) => true,
+            _ => false,
+        };
+    }
 }
@@ -2,8 +2,11 @@
 
 use dijkstra_map::*;
 use grid::*;
+use jobs::Role;
 use statics::*;
+use std::cmp;
 use std::collections::BTreeMap;
+use utils::*;
 
 // `from_grid` helper.
 macro_rules! s{
@@ -19,21 +22,111 @@ pub enum Templates {
     GeneralStore,
 }
 
+/// Shapes which can be painted with a single type of `Static`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Shape {
+    /// A straight line between the two points.
+    Line,
+    /// The border of the rectangle with the two points as opposite corners.
+    Outline,
+    /// The rectangle with the two points as opposite corners, filled in.
+    Filled,
+}
+
 /// A template is a list of statics objects to place, with (0,0) being the top-left corner of the
 /// template.
 #[derive(Clone, Debug)]
 pub struct Template {
     /// The components of the template, including possible maptags to place.
     pub components: BTreeMap<Point, (Static, Option<MapTag>)>,
+    /// The cost of building the template, on top of the cost of its components. This covers
+    /// things like labour and fittings which aren't represented by a `Static`.
+    pub overhead: usize,
+    /// Which of the available templates this is, if it is one.
+    pub kind: Option<Templates>,
+}
+
+impl Templates {
+    /// The roles which need filling for a building of this type to run properly.
+    pub fn roles(&self) -> Vec<Role> {
+        match *self {
+            Templates::Inn => vec![Role::Innkeeper],
+            Templates::GeneralStore => vec![Role::Shopkeeper],
+        }
+    }
 }
 
 impl Template {
     /// Get a template.
     pub fn new(tpl: Templates) -> Template {
-        match tpl {
+        let mut template = match tpl {
             Templates::Inn => Template::inn(),
             Templates::GeneralStore => Template::general_store(),
+        };
+        template.kind = Some(tpl);
+        template
+    }
+
+    /// Make a template by painting a shape between two points with a single type of `Static`. The
+    /// template is returned along with the point its (0,0) corresponds to. Painted templates have
+    /// no overhead: it's just the cost of the materials.
+    pub fn paint(tag: StaticTag, shape: Shape, from: Point, to: Point) -> (Template, Point) {
+        let top_left = Point {
+            x: cmp::min(from.x, to.x),
+            y: cmp::min(from.y, to.y),
+        };
+        let bottom_right = Point {
+            x: cmp::max(from.x, to.x),
+            y: cmp::max(from.y, to.y),
+        };
+
+        let points = match shape {
+            Shape::Line => line(from, to),
+            Shape::Outline | Shape::Filled => {
+                let mut points = Vec::new();
+                for y in top_left.y..bottom_right.y + 1 {
+                    for x in top_left.x..bottom_right.x + 1 {
+                        if shape == Shape::Filled || y == top_left.y || y == bottom_right.y ||
+                           x == top_left.x || x == bottom_right.x {
+                            points.push(Point { x: x, y: y });
+                        }
+                    }
+                }
+                points
+            }
+        };
+
+        let s = Static::new(tag);
+        let mut components = BTreeMap::new();
+        for p in points {
+            let off = Point {
+                x: p.x - top_left.x,
+                y: p.y - top_left.y,
+            };
+            let _ = components.insert(off, (s, s.maptag()));
+        }
+
+        (Template {
+            components: components,
+            overhead: 0,
+            kind: None,
+        },
+         top_left)
+    }
+
+    /// The bottom-right corner of the template.
+    pub fn bottom_right(&self) -> Point {
+        let mut corner = Point { x: 0, y: 0 };
+        for point in self.components.keys() {
+            corner.x = cmp::max(corner.x, point.x);
+            corner.y = cmp::max(corner.y, point.y);
         }
+        corner
+    }
+
+    /// The total cost of building the template.
+    pub fn cost(&self) -> usize {
+        self.components.values().fold(self.overhead, |acc, &(s, _)| acc + s.cost())
     }
 
     /// Rotate 90 degrees clockwise.
@@ -93,7 +186,8 @@ impl Template {
          *    #   #
          *    ##║##
          */
-        from_grid(&[vec![s!(Wall),     s!(Wall),       s!(Wall),      s!(Wall),s!(Wall)],
+        from_grid(50,
+                  &[vec![s!(Wall),     s!(Wall),       s!(Wall),      s!(Wall),s!(Wall)],
                     vec![s!(Wall),       None,           None,          None,  s!(Wall)],
                     vec![s!(Wall),s!(GStoreCounter),s!(GStoreCounter),s!(Door),s!(Wall)],
                     vec![s!(Wall),       None,           None,          None,  s!(Wall)],
@@ -111,7 +205,8 @@ impl Template {
          *            ║   #
          *            ######
          */
-        from_grid(&[vec![s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),   s!(Wall),   s!(Wall),s!(Wall)],
+        from_grid(100,
+                  &[vec![s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),   s!(Wall),   s!(Wall),s!(Wall)],
                     vec![s!(Wall),s!(Bed),    None, s!(Bed),   None,  s!(Bed), s!(Wall),  None,    None,    None,    None,        None,      None,  s!(Wall)],
                     vec![s!(Wall),  None,     None,   None,    None,    None,  s!(Door),  None,    None,    None,    None,  s!(InnCounter),  None,  s!(Wall)],
                     vec![s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),s!(Wall),  None,    None,    None,    None,  s!(InnCounter),  None,  s!(Wall)],
@@ -121,7 +216,7 @@ impl Template {
 }
 
 /// Construct components from a grid. TODO: make this a macro.
-fn from_grid(grid: &[Vec<Option<StaticTag>>]) -> Template {
+fn from_grid(overhead: usize, grid: &[Vec<Option<StaticTag>>]) -> Template {
     let mut components = BTreeMap::new();
 
     for y in 0..grid.len() {
@@ -133,5 +228,9 @@ fn from_grid(grid: &[Vec<Option<StaticTag>>]) -> Template {
         }
     }
 
-    Template { components: components }
+    Template {
+        components: components,
+        overhead: overhead,
+        kind: None,
+    }
 }
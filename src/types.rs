@@ -1,12 +1,21 @@
 //! All the types. This is just a placeholder module as things get implemented and spread out into
 //! their own modules.
 
+use constants::*;
 use dijkstra_map::*;
 use grid::*;
+use jobs::Job;
+use language::Language;
+use mobiles::{Effect, Mobile};
+use mobiles::attributes::Attribute;
+use mobiles::skills::{Outcome, TaskKind};
+use mobiles::training::Training;
+use rand::Rng;
 use statics::*;
 use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use templates::*;
+use utils::{is_occupied, nearest_free};
 
 /// A command from the user.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -19,15 +28,37 @@ pub enum Command {
     Render,
     /// Change the selected cell.
     SetCursorTo(Point),
+    /// Paint a shape between two points with the given type of `Static`.
+    Paint(StaticTag, Shape, Point, Point),
+    /// Redo the most recently undone construction.
+    Redo,
+    /// Open or close the door at the given point.
+    ToggleDoor(Point),
+    /// Lock or unlock the door at the given point.
+    ToggleLock(Point),
     /// Change the active template.
     SetTemplateTo(Templates),
     /// Advance one turn.
     Step,
+    /// Undo the most recent construction.
+    Undo,
 }
 
 /// The state of the vsible map and the larger game world.
 #[derive(Debug)]
 pub struct World {
+    /// The name of the town.
+    pub name: String,
+    /// The language spoken in the town.
+    pub lang: Language,
+    /// The lands beyond the map which travellers come from, each with its own language.
+    pub foreign: Vec<Homeland>,
+    /// The training packages mobs can take.
+    pub training: Training,
+    /// Named areas of the world.
+    pub places: Vec<Place>,
+    /// The jobs in the town's buildings.
+    pub jobs: Vec<Job>,
     /// Things which have a fixed presence and location in the world.
     pub statics: Grid<Option<Static>>,
     /// Heatmap sources.
@@ -38,17 +69,51 @@ pub struct World {
     pub cursor: Point,
     /// Selected template.
     pub template: Option<Template>,
+    /// Gold in the town treasury.
+    pub treasury: usize,
+    /// Every change to the treasury, most recent last.
+    pub ledger: Vec<Transaction>,
+    /// Construction which can be undone, most recent last.
+    pub undo: VecDeque<Edit>,
+    /// Construction which has been undone and can be redone, most recent last.
+    pub redo: Vec<Edit>,
+    /// The maximum number of entries kept in `undo`.
+    pub undo_depth: usize,
+    /// The number of turns since the game began.
+    pub turn: usize,
+    /// Effects mobs have had on each other this turn, and the ids of the mobs affected.
+    pub effects: Vec<(usize, Effect)>,
+    /// The id to give to the next mob generated.
+    pub next_id: usize,
+    /// Tax owed since the treasury last collected it.
+    pub taxes: usize,
 }
 
 impl World {
-    /// Construct a new world.
-    pub fn new() -> World {
+    /// Construct a new world, where the given language is spoken and mobs can take the given
+    /// training packages.
+    pub fn new(lang: Language, training: Training) -> World {
         World {
+            name: String::new(),
+            lang: lang,
+            foreign: Vec::new(),
+            training: training,
+            places: Vec::new(),
+            jobs: Vec::new(),
             statics: Grid::new(None),
             sources: BTreeMap::new(),
             messages: VecDeque::new(),
             cursor: Point { x: 0, y: 0 },
             template: None,
+            treasury: STARTING_GOLD,
+            ledger: Vec::new(),
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            undo_depth: UNDO_DEPTH,
+            turn: 0,
+            effects: Vec::new(),
+            next_id: 0,
+            taxes: 0,
         }
     }
 
@@ -58,29 +123,588 @@ impl World {
     }
 
     /// Do a turn.
-    pub fn step(&mut self) {}
+    pub fn step(&mut self) {
+        self.turn += 1;
+    }
+
+    /// The number of days since the game began.
+    pub fn day(&self) -> usize {
+        self.turn / TURNS_PER_DAY
+    }
+
+    /// The day of the current year, starting from zero.
+    pub fn day_of_year(&self) -> usize {
+        self.day() % DAYS_PER_YEAR
+    }
+
+    /// The number of years since the game began.
+    pub fn year(&self) -> usize {
+        self.day() / DAYS_PER_YEAR
+    }
+
+    /// The hour of the day, starting from zero at midnight.
+    pub fn hour(&self) -> usize {
+        (self.turn % TURNS_PER_DAY) * HOURS_PER_DAY / TURNS_PER_DAY
+    }
+
+    /// The next turn (after this one) at which the given hour starts.
+    pub fn next_hour(&self, hour: usize) -> usize {
+        let turn = self.day() * TURNS_PER_DAY + hour * TURNS_PER_DAY / HOURS_PER_DAY;
+        if turn > self.turn {
+            turn
+        } else {
+            turn + TURNS_PER_DAY
+        }
+    }
+
+    /// Whether this turn is the first of a new hour.
+    pub fn is_new_hour(&self) -> bool {
+        let hour_of = |turn: usize| (turn % TURNS_PER_DAY) * HOURS_PER_DAY / TURNS_PER_DAY;
+        self.turn == 0 || hour_of(self.turn) != hour_of(self.turn - 1)
+    }
+
+    /// Whether this turn is the first of a new day.
+    pub fn is_new_day(&self) -> bool {
+        self.turn % TURNS_PER_DAY == 0
+    }
+
+    /// Generate a travelling adventurer from one of the foreign lands. If there are none, they're
+    /// a local.
+    pub fn gen_traveller<R: Rng>(&mut self, rng: &mut R) -> Mobile {
+        if self.foreign.is_empty() {
+            return Mobile::gen_adventurer(rng, &self.training, &mut self.next_id, &mut self.lang);
+        }
+        let i = rng.gen_range(0, self.foreign.len());
+        Mobile::gen_traveller(rng, &self.training, &mut self.next_id, &mut self.foreign[i])
+    }
+
+    /// A travelling adventurer arrives in town at the given point, and makes it their home while
+    /// they're here. Nobody arrives if the point is occupied. Returns `false` if nobody arrived.
+    pub fn welcome_traveller<R: Rng>(&mut self,
+                                     rng: &mut R,
+                                     mobs: &mut BTreeMap<Point, Mobile>,
+                                     at: Point)
+                                     -> bool {
+        if is_occupied(at, mobs, self) {
+            return false;
+        }
+
+        let mut mob = self.gen_traveller(rng);
+        mob.home_pos = at;
+        let msg = match mob.origin {
+            Some(ref origin) => {
+                format!("{}, an adventurer from {}, has arrived in {}.",
+                        mob.name,
+                        origin,
+                        self.name)
+            }
+            None => format!("{}, an adventurer, has arrived in {}.", mob.name, self.name),
+        };
+        self.log(Message {
+            msg: msg,
+            loc: Some(at),
+        });
+        let _ = mobs.insert(at, mob);
+        true
+    }
+
+    /// A household settles in town, each member making their home at the nearest free point to the
+    /// given one. Returns the number who settled, which is fewer than the whole household if the
+    /// map is full.
+    pub fn settle_household<R: Rng>(&mut self,
+                                    rng: &mut R,
+                                    mobs: &mut BTreeMap<Point, Mobile>,
+                                    near: Point)
+                                    -> usize {
+        let family = Mobile::gen_family(rng, &self.training, &mut self.next_id, &mut self.lang);
+        let msg = format!("{} and their family have settled in {}.", family[0].name, self.name);
+        let mut settled = 0;
+        for mut mob in family {
+            let pos = match nearest_free(near, mobs, self) {
+                Some(pos) => pos,
+                None => break,
+            };
+            mob.home_pos = pos;
+            let _ = mobs.insert(pos, mob);
+            settled += 1;
+        }
+        if settled > 0 {
+            self.log(Message {
+                msg: msg,
+                loc: Some(near),
+            });
+        }
+        settled
+    }
+
+    /// Hold back an effect on a mob until every mob has had their turn.
+    pub fn affect(&mut self, id: usize, effect: Effect) {
+        self.effects.push((id, effect));
+    }
+
+    /// Apply the effects mobs have had on each other this turn.
+    pub fn apply_effects<R: Rng>(&mut self, rng: &mut R, mobs: &mut BTreeMap<Point, Mobile>) {
+        for (id, effect) in ::std::mem::replace(&mut self.effects, Vec::new()) {
+            for mob in mobs.values_mut().filter(|m| m.id == id) {
+                match effect {
+                    Effect::Practise(attribute, amount) => {
+                        let _ = mob.practise(&self.training, attribute, amount);
+                    }
+                    Effect::Experience(ref event) => {
+                        let _ = mob.experience(rng, event.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// The smallest named area containing a point, if there is one.
+    pub fn place_at(&self, pos: Point) -> Option<&Place> {
+        let mut best: Option<&Place> = None;
+        for place in &self.places {
+            if place.contains(pos) && best.map_or(true, |b| place.area() < b.area()) {
+                best = Some(place);
+            }
+        }
+        best
+    }
+
+    /// Owe some tax, to be paid in when the treasury next collects it.
+    pub fn tax(&mut self, amount: usize) {
+        self.taxes += amount;
+    }
+
+    /// Pay all the tax owed into the treasury, as a single entry in the ledger.
+    pub fn collect_taxes(&mut self) {
+        let taxes = self.taxes;
+        if taxes > 0 {
+            self.taxes = 0;
+            self.earn(taxes, "the day's taxes".to_string());
+        }
+    }
+
+    /// Add gold to the treasury, and record it in the ledger.
+    pub fn earn(&mut self, amount: usize, reason: String) {
+        self.treasury += amount;
+        self.log(Message {
+            msg: format!("Received {} gold: {}.", amount, reason),
+            loc: None,
+        });
+        self.ledger.push(Transaction::Income {
+            amount: amount,
+            reason: reason,
+        });
+    }
+
+    /// Take gold from the treasury, and record it in the ledger. If there isn't enough gold,
+    /// nothing is taken and `false` is returned.
+    pub fn spend(&mut self, amount: usize, reason: String) -> bool {
+        if amount > self.treasury {
+            self.log(Message {
+                msg: format!("Cannot afford {}: it costs {} gold, but the treasury holds {}.",
+                             reason,
+                             amount,
+                             self.treasury),
+                loc: None,
+            });
+            return false;
+        }
+
+        self.treasury -= amount;
+        self.log(Message {
+            msg: format!("Spent {} gold: {}.", amount, reason),
+            loc: None,
+        });
+        self.ledger.push(Transaction::Expense {
+            amount: amount,
+            reason: reason,
+        });
+        true
+    }
+
+    /// Build the active template at the cursor, if the treasury can pay for it. Buildings are
+    /// given a name. Returns `false` if nothing was built.
+    pub fn build<R: Rng>(&mut self, rng: &mut R, maps: &mut Maps) -> bool {
+        let tpl = match self.template.clone() {
+            Some(tpl) => tpl,
+            None => return false,
+        };
+
+        let pos = self.cursor;
+        let building = match tpl.kind {
+            Some(kind) => {
+                let name = match kind {
+                    Templates::Inn => format!("The {} Inn", self.lang.gen_place(rng)),
+                    Templates::GeneralStore => {
+                        format!("{} General Store", self.lang.gen_place(rng))
+                    }
+                };
+                Some(Place {
+                    name: name,
+                    kind: PlaceKind::Building(kind),
+                    top_left: pos,
+                    bottom_right: tpl.bottom_right().offset(pos),
+                })
+            }
+            None => None,
+        };
+        let reason = match building {
+            Some(ref b) => format!("building {}", b.name),
+            None => "construction".to_string(),
+        };
+
+        if !self.spend(tpl.cost(), reason) {
+            return false;
+        }
+
+        let mut edit = self.place(maps, &tpl, pos);
+        edit.cost = tpl.cost();
+        if let Some(b) = building {
+            self.log(Message {
+                msg: format!("{} has opened its doors.", b.name),
+                loc: Some(pos),
+            });
+            self.places.push(b.clone());
+            edit.place = Some(b);
+        }
+        self.record(edit);
+        true
+    }
+
+    /// Paint a shape with a single type of `Static`, if the treasury can pay for it. Returns
+    /// `false` if nothing was painted.
+    pub fn paint(&mut self,
+                 maps: &mut Maps,
+                 tag: StaticTag,
+                 shape: Shape,
+                 from: Point,
+                 to: Point)
+                 -> bool {
+        let (tpl, pos) = Template::paint(tag, shape, from, to);
+
+        if !self.spend(tpl.cost(), "construction".to_string()) {
+            return false;
+        }
+
+        let mut edit = self.place(maps, &tpl, pos);
+        edit.cost = tpl.cost();
+        self.record(edit);
+        true
+    }
 
-    /// Build the active template at the cursor.
-    pub fn build(&mut self, maps: &mut Maps) {
-        if let Some(ref tpl) = self.template {
-            for (p, &(s, t)) in &tpl.components {
-                let pos = p.offset(self.cursor);
+    /// Undo the most recent construction, refunding its cost. Returns `false` if there was nothing
+    /// to undo.
+    pub fn undo(&mut self, maps: &mut Maps) -> bool {
+        match self.undo.pop_back() {
+            Some(edit) => {
+                let inverse = self.restore(maps, &edit);
+                if edit.cost > 0 {
+                    self.earn(edit.cost, "refund for undone construction".to_string());
+                }
+                self.redo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the most recently undone construction, if the treasury can still pay for it. Returns
+    /// `false` if nothing was redone.
+    pub fn redo(&mut self, maps: &mut Maps) -> bool {
+        match self.redo.pop() {
+            Some(edit) => {
+                if edit.cost > 0 && !self.spend(edit.cost, "construction".to_string()) {
+                    self.redo.push(edit);
+                    return false;
+                }
+                let inverse = self.restore(maps, &edit);
+                self.undo.push_back(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record a new construction in the undo history. This forgets anything which could have been
+    /// redone, and the oldest entries if the history is too long.
+    fn record(&mut self, edit: Edit) {
+        self.undo.push_back(edit);
+        while self.undo.len() > self.undo_depth {
+            let _ = self.undo.pop_front();
+        }
+        self.redo.clear();
+    }
+
+    /// Open a closed door, or close an open one, as an edit which can be undone. Returns `false` if
+    /// there is no such door.
+    pub fn toggle_door(&mut self, maps: &mut Maps, pos: Point) -> bool {
+        let state = match self.statics.at(pos).and_then(|s| s.door) {
+            Some(DoorState::Open) => DoorState::Closed,
+            Some(DoorState::Closed) => DoorState::Open,
+            _ => return false,
+        };
+        self.edit_door(maps, pos, state)
+    }
+
+    /// Lock a door, or unlock a locked one, as an edit which can be undone. Returns `false` if
+    /// there is no door.
+    pub fn toggle_lock(&mut self, maps: &mut Maps, pos: Point) -> bool {
+        let state = match self.statics.at(pos).and_then(|s| s.door) {
+            Some(DoorState::Locked) => DoorState::Closed,
+            Some(_) => DoorState::Locked,
+            None => return false,
+        };
+        self.edit_door(maps, pos, state)
+    }
+
+    /// Change the state of the door at a point, and record it in the undo history.
+    fn edit_door(&mut self, maps: &mut Maps, pos: Point, state: DoorState) -> bool {
+        let old = (pos, self.statics.at(pos), self.sources.get(&pos).cloned());
+        if !self.set_door(maps, pos, state) {
+            return false;
+        }
+        self.record(Edit {
+            cells: vec![old],
+            cost: 0,
+            place: None,
+        });
+        true
+    }
+
+    /// Change the state of the door at a point. If this changes whether the door can be passed
+    /// through, the heatmaps are rebuilt. Returns `false` if there is no door.
+    pub fn set_door(&mut self, maps: &mut Maps, pos: Point, state: DoorState) -> bool {
+        if let Some(mut s) = self.statics.at(pos) {
+            let was_impassable = s.is_impassable;
+            if s.set_door(state) {
                 self.statics.set(pos, Some(s));
-                if let Some(old_tag) = self.sources.get(&pos) {
-                    maps.mutget(*old_tag).remove_source_no_rebuild(pos);
+                if s.is_impassable != was_impassable {
+                    maps.rebuild_all(self);
                 }
-                let _ = self.sources.remove(&pos);
-                if let Some(tag) = t {
-                    // Rebuild the maps at the end.
-                    maps.mutget(tag).add_source_no_rebuild(pos);
-                    let _ = self.sources.insert(pos, tag);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Use the static at a point, wearing it down. If this destroys it, it is removed from the
+    /// world. Returns `false` if there is nothing there.
+    pub fn use_static(&mut self, maps: &mut Maps, pos: Point) -> bool {
+        match self.statics.at(pos) {
+            Some(mut s) => {
+                if s.use_once() {
+                    self.statics.set(pos, Some(s));
+                } else {
+                    self.set_cell_no_rebuild(maps, pos, None, None);
+                    maps.rebuild_all(self);
+                    let msg = match self.place_at(pos) {
+                        Some(place) => {
+                            format!("A {:?} in {} has worn out and fallen apart.",
+                                    s.tag,
+                                    place.name)
+                        }
+                        None => format!("A {:?} has worn out and fallen apart.", s.tag),
+                    };
+                    self.log(Message {
+                        msg: msg,
+                        loc: Some(pos),
+                    });
                 }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Have a mob repair the static at a point, such as a worker mending their counter. How well it
+    /// goes depends on their skill: a critical success restores twice as much, and a critical
+    /// failure does a little damage (though never enough to destroy it). Returns the number of hit
+    /// points restored.
+    pub fn repair<R: Rng>(&mut self, rng: &mut R, pos: Point, mob: &Mobile) -> usize {
+        match self.statics.at(pos) {
+            Some(mut s) => {
+                let craft = mob.attributes.get(Attribute::Craft);
+                let restored = match mob.attempt(rng, TaskKind::Craft, REPAIR_DIFFICULTY, false) {
+                    Outcome::CriticalSuccess => s.repair(craft * 2),
+                    Outcome::Success => s.repair(craft),
+                    Outcome::Failure => 0,
+                    Outcome::CriticalFailure => {
+                        if s.hp > 1 {
+                            let _ = s.damage(1);
+                        }
+                        0
+                    }
+                };
+                self.statics.set(pos, Some(s));
+                restored
+            }
+            None => 0,
+        }
+    }
+
+    /// Place a template with its (0,0) at the given point, and rebuild the heatmaps. Components
+    /// which would fall off the edge of the world are skipped. Returns the prior contents of the
+    /// affected cells.
+    pub fn place(&mut self, maps: &mut Maps, tpl: &Template, at: Point) -> Edit {
+        let mut edit = Edit {
+            cells: Vec::new(),
+            cost: 0,
+            place: None,
+        };
+
+        for (p, &(s, t)) in &tpl.components {
+            let pos = p.offset(at);
+            if pos.x >= WIDTH || pos.y >= HEIGHT {
+                continue;
+            }
+            edit.cells.push((pos, self.statics.at(pos), self.sources.get(&pos).cloned()));
+            self.set_cell_no_rebuild(maps, pos, Some(s), t);
+        }
+        maps.rebuild_all(self);
+
+        edit
+    }
+
+    /// Put back the prior contents of the cells changed by an edit, and rebuild the heatmaps.
+    /// Returns the edit which would reverse this.
+    pub fn restore(&mut self, maps: &mut Maps, edit: &Edit) -> Edit {
+        let mut inverse = Edit {
+            cells: Vec::new(),
+            cost: edit.cost,
+            place: edit.place.clone(),
+        };
+
+        // Go backwards, so that if a cell appears more than once it ends up as it was first.
+        for &(pos, s, t) in edit.cells.iter().rev() {
+            inverse.cells.push((pos, self.statics.at(pos), self.sources.get(&pos).cloned()));
+            self.set_cell_no_rebuild(maps, pos, s, t);
+        }
+        maps.rebuild_all(self);
+
+        if let Some(ref place) = edit.place {
+            if self.places.contains(place) {
+                self.places.retain(|p| p != place);
+            } else {
+                self.places.push(place.clone());
             }
-            maps.rebuild_all(self);
+        }
+
+        inverse
+    }
+
+    /// Change the static and heatmap source at a point. The heatmaps will need rebuilding
+    /// afterwards. Statics which need staff are only added to their heatmap if someone is on duty.
+    fn set_cell_no_rebuild(&mut self,
+                           maps: &mut Maps,
+                           pos: Point,
+                           s: Option<Static>,
+                           t: Option<MapTag>) {
+        self.statics.set(pos, s);
+        if let Some(old_tag) = self.sources.remove(&pos) {
+            maps.mutget(old_tag).remove_source_no_rebuild(pos);
+        }
+        if let Some(tag) = t {
+            // Things which need staff aren't sources until someone is working at them.
+            if !s.map_or(false, |s| s.needs_staff()) || self.is_staffed(pos) {
+                maps.mutget(tag).add_source_no_rebuild(pos);
+            }
+            let _ = self.sources.insert(pos, tag);
         }
     }
 }
 
+/// A change to the world which can be undone.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Edit {
+    /// The prior static and heatmap source of every cell changed.
+    pub cells: Vec<(Point, Option<Static>, Option<MapTag>)>,
+    /// The gold spent on the change.
+    pub cost: usize,
+    /// A named place created by the change, which is removed when it is undone (and put back when
+    /// that is undone).
+    pub place: Option<Place>,
+}
+
+/// A land beyond the edge of the map, and the language spoken there.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Homeland {
+    /// The name of the land.
+    pub name: String,
+    /// The language spoken there.
+    pub lang: Language,
+}
+
+impl Homeland {
+    /// Generate a new land, with a language of its own.
+    pub fn new<R: Rng>(rng: &mut R) -> Homeland {
+        let mut lang = Language::new(rng);
+        let name = lang.gen_region(rng);
+        Homeland {
+            name: name,
+            lang: lang,
+        }
+    }
+}
+
+/// A named area of the world.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Place {
+    /// The name.
+    pub name: String,
+    /// What sort of place this is.
+    pub kind: PlaceKind,
+    /// The top-left corner of the area.
+    pub top_left: Point,
+    /// The bottom-right corner of the area.
+    pub bottom_right: Point,
+}
+
+impl Place {
+    /// Whether a point is in this place.
+    pub fn contains(&self, pos: Point) -> bool {
+        pos.x >= self.top_left.x && pos.x <= self.bottom_right.x && pos.y >= self.top_left.y &&
+        pos.y <= self.bottom_right.y
+    }
+
+    /// The number of cells in this place.
+    pub fn area(&self) -> usize {
+        (1 + self.bottom_right.x - self.top_left.x) * (1 + self.bottom_right.y - self.top_left.y)
+    }
+}
+
+/// The sorts of named places.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum PlaceKind {
+    /// The town.
+    Town,
+    /// The dungeon entrance.
+    Dungeon,
+    /// A natural feature of the land, like a forest or a river.
+    Region,
+    /// A building built from one of the templates.
+    Building(Templates),
+}
+
+/// A change to the town treasury.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Transaction {
+    /// Gold coming in.
+    Income {
+        /// How much.
+        amount: usize,
+        /// What for.
+        reason: String,
+    },
+    /// Gold going out.
+    Expense {
+        /// How much.
+        amount: usize,
+        /// What for.
+        reason: String,
+    },
+}
+
 /// A message consists of some text and an optional location. The UI intelligently handle the
 /// location (eg, jump-to-location).
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -90,3 +714,138 @@ pub struct Message {
     /// The optional location.
     pub loc: Option<Point>,
 }
+
+#[cfg(test)]
+mod tests {
+    use constants::{DEFAULT_TRAINING, HOURS_PER_DAY, STARTING_GOLD, TURNS_PER_DAY};
+    use dijkstra_map::Maps;
+    use grid::Point;
+    use language::Language;
+    use mobiles::training::Training;
+    use rand::{SeedableRng, StdRng};
+    use statics::{DoorState, StaticTag};
+    use super::World;
+    use templates::Shape;
+
+    /// A new, empty, world.
+    fn new_world() -> World {
+        let mut rng: StdRng = SeedableRng::from_seed(&[0][..]);
+        let training = Training::parse(DEFAULT_TRAINING).unwrap();
+        World::new(Language::new(&mut rng), training)
+    }
+
+    /// Paint a short wall, starting at the given point.
+    fn paint_wall(world: &mut World, maps: &mut Maps, x: usize) -> bool {
+        world.paint(maps,
+                    StaticTag::Wall,
+                    Shape::Line,
+                    Point { x: x, y: 1 },
+                    Point { x: x, y: 3 })
+    }
+
+    #[test]
+    fn undo_refunds() {
+        let mut world = new_world();
+        let mut maps = Maps::new();
+        assert!(paint_wall(&mut world, &mut maps, 1));
+        assert!(world.treasury < STARTING_GOLD);
+        assert!(world.statics.at(Point { x: 1, y: 2 }).is_some());
+
+        assert!(world.undo(&mut maps));
+        assert_eq!(world.treasury, STARTING_GOLD);
+        assert!(world.statics.at(Point { x: 1, y: 2 }).is_none());
+
+        assert!(world.redo(&mut maps));
+        assert!(world.treasury < STARTING_GOLD);
+        assert!(world.statics.at(Point { x: 1, y: 2 }).is_some());
+    }
+
+    #[test]
+    fn taxes_collected_at_once() {
+        let mut world = new_world();
+        world.tax(1);
+        world.tax(1);
+        assert_eq!(world.treasury, STARTING_GOLD);
+        assert!(world.ledger.is_empty());
+
+        world.collect_taxes();
+        assert_eq!(world.treasury, STARTING_GOLD + 2);
+        assert_eq!(world.ledger.len(), 1);
+
+        world.collect_taxes();
+        assert_eq!(world.ledger.len(), 1);
+    }
+
+    #[test]
+    fn undo_depth_limited() {
+        let mut world = new_world();
+        let mut maps = Maps::new();
+        world.undo_depth = 2;
+        for x in 1..4 {
+            assert!(paint_wall(&mut world, &mut maps, x));
+        }
+        assert!(world.undo(&mut maps));
+        assert!(world.undo(&mut maps));
+        assert!(!world.undo(&mut maps));
+        assert!(world.statics.at(Point { x: 1, y: 2 }).is_some());
+    }
+
+    #[test]
+    fn redo_forgotten_on_new_construction() {
+        let mut world = new_world();
+        let mut maps = Maps::new();
+        assert!(paint_wall(&mut world, &mut maps, 1));
+        assert!(world.undo(&mut maps));
+        assert!(paint_wall(&mut world, &mut maps, 2));
+        assert!(!world.redo(&mut maps));
+        assert!(world.statics.at(Point { x: 1, y: 2 }).is_none());
+    }
+
+    #[test]
+    fn undo_door_is_free() {
+        let mut world = new_world();
+        let mut maps = Maps::new();
+        let door = Point { x: 1, y: 1 };
+        assert!(world.paint(&mut maps, StaticTag::Door, Shape::Line, door, door));
+        let (treasury, ledger) = (world.treasury, world.ledger.len());
+        let state = |world: &World| world.statics.at(door).and_then(|s| s.door);
+
+        assert!(world.toggle_door(&mut maps, door));
+        assert_eq!(state(&world), Some(DoorState::Open));
+        assert!(world.undo(&mut maps));
+        assert_eq!(state(&world), Some(DoorState::Closed));
+        assert!(world.redo(&mut maps));
+        assert_eq!(state(&world), Some(DoorState::Open));
+        assert_eq!(world.treasury, treasury);
+        assert_eq!(world.ledger.len(), ledger);
+    }
+
+    #[test]
+    fn new_day_once_a_day() {
+        let mut world = new_world();
+        world.step();
+
+        let mut new_days = 0;
+        for _ in 0..TURNS_PER_DAY {
+            if world.is_new_day() {
+                new_days += 1;
+            }
+            world.step();
+        }
+        assert_eq!(new_days, 1);
+    }
+
+    #[test]
+    fn new_hour_every_hour() {
+        let mut world = new_world();
+
+        let mut new_hours = 0;
+        for _ in 0..TURNS_PER_DAY {
+            if world.is_new_hour() {
+                new_hours += 1;
+            }
+            world.step();
+        }
+        assert_eq!(new_hours, HOURS_PER_DAY);
+    }
+}
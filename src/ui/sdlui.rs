@@ -84,6 +84,12 @@ pub struct SdlUI {
     is_paused: bool,
     /// What to display in the sidebar.
     menu: Menu,
+    /// Paint mode: the type of static and shape to paint.
+    brush: (StaticTag, Shape),
+    /// Paint mode: the start of the shape being painted, if one has been started.
+    anchor: Option<Point>,
+    /// Paint mode: whether the shape is being dragged out with the mouse.
+    is_dragging: bool,
     /// Increments (wrapping) on every frame.
     indicator: u8,
 }
@@ -92,6 +98,7 @@ pub struct SdlUI {
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 enum Menu {
     Main,
+    Paint,
     Template,
 }
 
@@ -133,7 +140,7 @@ impl UI for SdlUI {
         self.render_log(world);
 
         // Render the help/control sidebar.
-        self.render_sidebar();
+        self.render_sidebar(mobs, world);
 
         // Render the world OR heatmap.
         self.render_world(mobs, maps, world);
@@ -143,6 +150,13 @@ impl UI for SdlUI {
             self.render_template(world.cursor, &tpl)
         }
 
+        // Render the shape being painted.
+        if let (Menu::Paint, Some(anchor)) = (self.menu, self.anchor) {
+            let (tag, shape) = self.brush;
+            let (tpl, pos) = Template::paint(tag, shape, anchor, world.cursor);
+            self.render_template(pos, &tpl)
+        }
+
         // Display the cursor on top of everything else.
         self.render_cursor(world.cursor);
 
@@ -231,6 +245,9 @@ impl SdlUI {
             is_scrolling: false,
             is_paused: true,
             menu: Menu::Main,
+            brush: (StaticTag::Wall, Shape::Line),
+            anchor: None,
+            is_dragging: false,
             indicator: 0,
         })
     }
@@ -250,6 +267,12 @@ impl SdlUI {
         macro_rules! flag_unset {
             ( $var:ident ) => ( { self.$var = false; Command::Render } )
         }
+        macro_rules! brush_tag {
+            ( $tag:ident ) => ( { self.brush.0 = StaticTag::$tag; Command::Render } )
+        }
+        macro_rules! brush_shape {
+            ( $shape:ident ) => ( { self.brush.1 = Shape::$shape; Command::Render } )
+        }
 
         let step = if self.is_zooming { 10 } else { 1 };
 
@@ -262,34 +285,64 @@ impl SdlUI {
 
             // Menu
             keydown!(B) => {
+                match self.menu {
+                    Menu::Main => {
+                        self.menu = Menu::Template;
+                        Command::Render
+                    }
+                    Menu::Paint => brush_tag!(Bed),
+                    Menu::Template => Command::Render,
+                }
+            }
+            keydown!(P) => {
                 if self.menu == Menu::Main {
-                    self.menu = Menu::Template
+                    self.menu = Menu::Paint
                 }
                 Command::Render
             }
             keydown!(G) => {
-                if self.menu == Menu::Template {
-                    Command::SetTemplateTo(Templates::GeneralStore)
-                } else {
-                    Command::Render
+                match self.menu {
+                    Menu::Paint => brush_tag!(GStoreCounter),
+                    Menu::Template => Command::SetTemplateTo(Templates::GeneralStore),
+                    Menu::Main => Command::Render,
                 }
             }
             keydown!(I) => {
-                if self.menu == Menu::Template {
-                    Command::SetTemplateTo(Templates::Inn)
-                } else {
-                    Command::Render
+                match self.menu {
+                    Menu::Paint => brush_tag!(InnCounter),
+                    Menu::Template => Command::SetTemplateTo(Templates::Inn),
+                    Menu::Main => Command::Render,
                 }
             }
+            keydown!(W) if self.menu == Menu::Paint => brush_tag!(Wall),
+            keydown!(D) if self.menu == Menu::Paint => brush_tag!(Door),
+            keydown!(T) if self.menu == Menu::Paint => brush_tag!(Road),
+            keydown!(E) if self.menu == Menu::Paint => brush_tag!(Well),
+            keydown!(H) if self.menu == Menu::Paint => brush_tag!(Shrine),
+            keydown!(A) if self.menu == Menu::Paint => brush_tag!(Anvil),
+            keydown!(S) if self.menu == Menu::Paint => brush_tag!(Stable),
+            keydown!(M) if self.menu == Menu::Paint => brush_tag!(MarketStall),
+            keydown!(L) if self.menu == Menu::Paint => brush_shape!(Line),
+            keydown!(O) if self.menu == Menu::Paint => brush_shape!(Outline),
+            keydown!(F) if self.menu == Menu::Paint => brush_shape!(Filled),
+            keydown!(O) if self.menu == Menu::Main => Command::ToggleDoor(cursor),
+            keydown!(K) if self.menu == Menu::Main => Command::ToggleLock(cursor),
+            keydown!(U) => Command::Undo,
+            keydown!(R) => Command::Redo,
             keydown!(Escape) => {
-                self.menu = Menu::Main;
+                if self.anchor.is_some() {
+                    self.anchor = None;
+                    self.is_dragging = false;
+                } else {
+                    self.menu = Menu::Main;
+                }
                 Command::Render
             }
             keydown!(Return) => {
-                if self.menu == Menu::Template {
-                    Command::BuildTemplate
-                } else {
-                    Command::Render
+                match self.menu {
+                    Menu::Paint => self.paint_at(cursor),
+                    Menu::Template => Command::BuildTemplate,
+                    Menu::Main => Command::Render,
                 }
             }
 
@@ -315,6 +368,21 @@ impl SdlUI {
             }
 
             // Cursor and Viewport
+            Event::MouseButtonDown { x, y, mouse_btn: MouseButton::Left, .. }
+                if self.menu == Menu::Paint => {
+                let pos = self.screen.cursor_from_mouse(x, y);
+                self.anchor = Some(pos);
+                self.is_dragging = true;
+                Command::SetCursorTo(pos)
+            }
+            Event::MouseButtonUp { x, y, mouse_btn: MouseButton::Left, .. } if self.is_dragging => {
+                self.is_dragging = false;
+                let pos = self.screen.cursor_from_mouse(x, y);
+                self.paint_at(pos)
+            }
+            Event::MouseMotion { x, y, .. } if self.is_dragging => {
+                Command::SetCursorTo(self.screen.cursor_from_mouse(x, y))
+            }
             Event::MouseButtonDown { x, y, mouse_btn: MouseButton::Left, .. } => {
                 self.is_mousing = !self.is_mousing;
                 if self.is_mousing {
@@ -401,6 +469,22 @@ impl SdlUI {
         }
     }
 
+    /// Paint mode: start a shape at the given point or, if one has already been started, paint it
+    /// out to the given point.
+    fn paint_at(&mut self, pos: Point) -> Command {
+        match self.anchor {
+            Some(anchor) => {
+                self.anchor = None;
+                let (tag, shape) = self.brush;
+                Command::Paint(tag, shape, anchor, pos)
+            }
+            None => {
+                self.anchor = Some(pos);
+                Command::Render
+            }
+        }
+    }
+
     /// Render the cursor.
     fn render_cursor(&mut self, cursor: Point) {
         if let Some(cursor_pos) = self.screen.to_screenpos(cursor) {
@@ -462,28 +546,100 @@ impl SdlUI {
         self.screen.render_border(ScreenRect::new(0, 0, log_width, log_height));
     }
 
-    /// Render the sidebar.
-    fn render_sidebar(&mut self) {
+    /// Render the sidebar. If there is a mob under the cursor, as much of their biography as fits
+    /// is shown at the bottom.
+    fn render_sidebar(&mut self, mobs: &BTreeMap<Point, Mobile>, world: &World) {
         let sidebar_x = self.screen.cell_width() - 2 * BORDER_THICKNESS - SIDEBAR_WIDTH;
         let sidebar_width = SIDEBAR_WIDTH + 2 * BORDER_THICKNESS;
         let sidebar_height = self.screen.cell_height();
 
         let controls = match self.menu {
             Menu::Main => {
-                vec![vec![("b", "Building")],
+                vec![vec![("b", "Building"), ("p", "Paint")],
+                     vec![("o", "Open/close door"), ("k", "Lock/unlock door")],
+                     vec![("u", "Undo"), ("r", "Redo")],
                      if self.is_paused {
                          vec![("SPC", "Resume"), (".", "Single-step")]
                      } else {
                          vec![("SPC", "Pause")]
                      }]
             }
+            Menu::Paint => {
+                vec![vec![("w", "Wall"),
+                          ("d", "Door"),
+                          ("b", "Bed"),
+                          ("g", "Store Counter"),
+                          ("i", "Inn Counter"),
+                          ("t", "Road"),
+                          ("e", "Well"),
+                          ("h", "Shrine"),
+                          ("a", "Anvil"),
+                          ("s", "Stable"),
+                          ("m", "Market Stall")],
+                     vec![("l", "Line"), ("o", "Outline"), ("f", "Filled")],
+                     vec![if self.anchor.is_some() {
+                              ("RET", "Paint to cursor")
+                          } else {
+                              ("RET", "Start at cursor")
+                          },
+                          ("ESC", "Return to main menu")],
+                     vec![("u", "Undo"), ("r", "Redo")]]
+            }
             Menu::Template => {
                 vec![vec![("g", "General Store"), ("i", "Inn")],
-                     vec![("RET", "Build at cursor"), ("ESC", "Return to main menu")]]
+                     vec![("RET", "Build at cursor"), ("ESC", "Return to main menu")],
+                     vec![("u", "Undo"), ("r", "Redo")]]
             }
         };
 
-        let mut y = 2;
+        // The town.
+        {
+            let pos = ScreenPos {
+                x: sidebar_x + 2,
+                y: 2,
+            };
+            let name = truncate(&world.name, SIDEBAR_WIDTH as usize - 2);
+            self.screen.render_text(vec![(name, Color::RGB(150, 200, 250))], None, pos);
+        }
+
+        // The treasury.
+        {
+            let pos = ScreenPos {
+                x: sidebar_x + 2,
+                y: 3,
+            };
+            let richtext = vec![("Treasury: ".to_string(), Color::RGB(255, 255, 255)),
+                                (format!("{}g", world.treasury), Color::RGB(255, 215, 0))];
+            self.screen.render_text(richtext, None, pos);
+        }
+
+        // The cost of the selected template.
+        if let Some(ref tpl) = world.template {
+            let pos = ScreenPos {
+                x: sidebar_x + 2,
+                y: 4,
+            };
+            let color = if tpl.cost() > world.treasury {
+                Color::RGB(255, 100, 100)
+            } else {
+                Color::RGB(255, 215, 0)
+            };
+            let richtext = vec![("Cost: ".to_string(), Color::RGB(255, 255, 255)),
+                                (format!("{}g", tpl.cost()), color)];
+            self.screen.render_text(richtext, None, pos);
+        }
+
+        // The place under the cursor.
+        if let Some(place) = world.place_at(world.cursor) {
+            let pos = ScreenPos {
+                x: sidebar_x + 2,
+                y: 5,
+            };
+            let name = truncate(&place.name, SIDEBAR_WIDTH as usize - 2);
+            self.screen.render_text(vec![(name, Color::RGB(200, 200, 200))], None, pos);
+        }
+
+        let mut y = 7;
         for cs in controls {
             for (key, text) in cs {
                 let pos = ScreenPos {
@@ -499,6 +655,50 @@ impl SdlUI {
             y += 1;
         }
 
+        // The active brush.
+        if self.menu == Menu::Paint {
+            let pos = ScreenPos {
+                x: sidebar_x + 2,
+                y: y,
+            };
+            let (tag, shape) = self.brush;
+            let richtext = vec![("Brush: ".to_string(), Color::RGB(255, 255, 255)),
+                                (format!("{:?} ({:?})", tag, shape), Color::RGB(150, 150, 255))];
+            self.screen.render_text(richtext, None, pos);
+            y += 2;
+        }
+
+        // The mob under the cursor.
+        if let Some(mob) = mobs.get(&world.cursor) {
+            let width = SIDEBAR_WIDTH as usize - 2;
+            let max_y = sidebar_height - 2 * BORDER_THICKNESS;
+            let pos = ScreenPos {
+                x: sidebar_x + 2,
+                y: y,
+            };
+            let name = truncate(&mob.name, width);
+            self.screen.render_text(vec![(name, Color::RGB(150, 200, 250))], None, pos);
+            y += 2;
+
+            let biography = mob.biography(&world.training, |id| {
+                mobs.values().find(|m| m.id == id).map(|m| m.name.clone())
+            });
+            'paragraphs: for paragraph in biography.split("\n\n") {
+                for line in wrap(paragraph, width) {
+                    if y >= max_y {
+                        break 'paragraphs;
+                    }
+                    let pos = ScreenPos {
+                        x: sidebar_x + 2,
+                        y: y,
+                    };
+                    self.screen.render_text(vec![(line, Color::RGB(200, 200, 200))], None, pos);
+                    y += 1;
+                }
+                y += 1;
+            }
+        }
+
         self.screen.render_border(ScreenRect::new(sidebar_x, 0, sidebar_width, sidebar_height));
     }
 
@@ -759,7 +959,7 @@ impl Screen {
         if let Some(bg) = background {
             let mut total_length = 0;
             for &(ref s, _) in &richtext {
-                total_length += s.len();
+                total_length += s.chars().count();
             }
             let srect = ScreenRect::new(screenpos.x, screenpos.y, total_length as u32, 1);
             self.fill_rect(srect, bg);
@@ -768,7 +968,7 @@ impl Screen {
         let mut srect = ScreenRect::new(screenpos.x, screenpos.y, 0, 1);
         for &(ref s, c) in &richtext {
             let texture = self.render_string(s, c);
-            srect.width = s.len() as u32;
+            srect.width = s.chars().count() as u32;
             self.render_in_rect(&texture, srect, false, true);
             srect.top_left.x += srect.width;
         }
@@ -776,7 +976,7 @@ impl Screen {
 
     /// Render a plain string into a `Texture`.
     fn render_string(&mut self, text: &String, color: Color) -> Texture {
-        self.render_bytes(text.as_bytes(), color)
+        self.render_bytes(&to_cp437(text), color)
     }
 
     /// Render some bytes with the bitmap font into a `Texture`.
@@ -944,7 +1144,26 @@ impl Visual for Static {
             StaticTag::Dungeon => (234, Color::RGB(129, 26, 26), Some(Color::RGB(66, 66, 111))),
             StaticTag::Bed => (233, Color::RGB(166, 128, 100), None),
             StaticTag::Wall => ('#' as u8, Color::RGB(0, 0, 0), Some(Color::RGB(133, 94, 66))),
-            StaticTag::Door => (186, Color::RGB(0, 0, 0), Some(Color::RGB(133, 94, 66))),
+            StaticTag::Door => {
+                match self.door {
+                    Some(DoorState::Open) => ('\'' as u8, Color::RGB(133, 94, 66), None),
+                    Some(DoorState::Locked) => {
+                        (186, Color::RGB(200, 50, 50), Some(Color::RGB(133, 94, 66)))
+                    }
+                    _ => (186, Color::RGB(0, 0, 0), Some(Color::RGB(133, 94, 66))),
+                }
+            }
+            StaticTag::Well => ('o' as u8, Color::RGB(100, 150, 255), Some(Color::RGB(90, 90, 90))),
+            StaticTag::Shrine => (197, Color::RGB(255, 215, 0), None),
+            StaticTag::Anvil => (227, Color::RGB(150, 150, 160), None),
+            StaticTag::Stable => ('h' as u8, Color::RGB(166, 128, 100), None),
+            StaticTag::MarketStall => (209, Color::RGB(200, 60, 60), None),
+            StaticTag::Road => (176, Color::RGB(120, 100, 70), None),
+            StaticTag::Grass => ('"' as u8, Color::RGB(40, 110, 40), None),
+            StaticTag::Tree => (6, Color::RGB(30, 150, 30), None),
+            StaticTag::Rock => (30, Color::RGB(130, 130, 130), Some(Color::RGB(60, 60, 60))),
+            StaticTag::ShallowWater => (247, Color::RGB(130, 180, 255), None),
+            StaticTag::DeepWater => (247, Color::RGB(130, 180, 255), Some(Color::RGB(20, 40, 120))),
         }
     }
 }
@@ -952,6 +1171,89 @@ impl Visual for Static {
 
 // ********** Utilities **********
 
+/// Convert a string to the code page of the bitmap font, CP437. Accented letters which aren't in
+/// CP437 lose their accent, and anything else unknown becomes a '?'.
+fn to_cp437(s: &String) -> Vec<u8> {
+    s.chars()
+        .map(|c| match c {
+            'Ç' => 128,
+            'ü' => 129,
+            'é' => 130,
+            'â' => 131,
+            'ä' => 132,
+            'à' => 133,
+            'å' => 134,
+            'ç' => 135,
+            'ê' => 136,
+            'ë' => 137,
+            'è' => 138,
+            'ï' => 139,
+            'î' => 140,
+            'ì' => 141,
+            'Ä' => 142,
+            'Å' => 143,
+            'É' => 144,
+            'ô' => 147,
+            'ö' => 148,
+            'ò' => 149,
+            'û' => 150,
+            'ù' => 151,
+            'ÿ' => 152,
+            'Ö' => 153,
+            'Ü' => 154,
+            'á' => 160,
+            'í' => 161,
+            'ó' => 162,
+            'ú' => 163,
+            'ñ' => 164,
+            'Ñ' => 165,
+            'Á' | 'Â' | 'À' => 'A' as u8,
+            'Ê' | 'Ë' | 'È' => 'E' as u8,
+            'Í' | 'Î' | 'Ï' | 'Ì' => 'I' as u8,
+            'Ó' | 'Ô' | 'Ò' => 'O' as u8,
+            'Ú' | 'Û' | 'Ù' => 'U' as u8,
+            _ if c.is_ascii() => c as u8,
+            _ => '?' as u8,
+        })
+        .collect()
+}
+
+/// Cut a string down to at most the given number of characters.
+fn truncate(s: &String, len: usize) -> String {
+    s.chars().take(len).collect()
+}
+
+/// Break a string into lines of at most the given number of characters, at spaces where possible.
+fn wrap(s: &str, len: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in s.split_whitespace() {
+        let mut word = word.to_string();
+        if word.chars().count() > len && !line.is_empty() {
+            lines.push(line);
+            line = String::new();
+        }
+        while word.chars().count() > len {
+            let rest = word.chars().skip(len).collect();
+            lines.push(word.chars().take(len).collect());
+            word = rest;
+        }
+        if line.is_empty() {
+            line = word;
+        } else if line.chars().count() + 1 + word.chars().count() <= len {
+            line.push(' ');
+            line.push_str(&word);
+        } else {
+            lines.push(line);
+            line = word;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
 /// Advance to the next active heatmap, or turn it off on the last one.
 fn next_heatmap(heatmap: (Style, MapTag)) -> (Style, MapTag) {
     match heatmap {
@@ -1,7 +1,9 @@
 //! Utility functions.
 
+use constants::*;
 use grid::*;
 use mobiles::*;
+use std::cmp;
 use std::collections::BTreeMap;
 use types::*;
 
@@ -29,11 +31,33 @@ pub fn signed_add(u: usize, s: i8) -> usize {
     }
 }
 
+/// Check if two points are the same or next to each other.
+pub fn is_adjacent(a: Point, b: Point) -> bool {
+    let d = |x: usize, y: usize| if x > y { x - y } else { y - x };
+    d(a.x, b.x) <= 1 && d(a.y, b.y) <= 1
+}
+
 /// Check if a position is occupied.
 pub fn is_occupied(pos: Point, mobs: &BTreeMap<Point, Mobile>, world: &World) -> bool {
     mobs.get(&pos).is_some() || world.statics.at(pos).map_or(false, |s| s.is_impassable)
 }
 
+/// Find the nearest unoccupied position to a point, if there is one. Distance is measured in
+/// moves, so the search goes out in squares.
+pub fn nearest_free(pos: Point, mobs: &BTreeMap<Point, Mobile>, world: &World) -> Option<Point> {
+    for r in 0..cmp::max(WIDTH, HEIGHT) {
+        for y in pos.y.saturating_sub(r)..cmp::min(pos.y + r + 1, HEIGHT) {
+            for x in pos.x.saturating_sub(r)..cmp::min(pos.x + r + 1, WIDTH) {
+                let p = Point { x: x, y: y };
+                if !is_occupied(p, mobs, world) {
+                    return Some(p);
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Check if a tile can be seen from another.
 pub fn can_see(start: Point, end: Point, world: &World) -> bool {
     let mut pos = start;
@@ -78,6 +102,63 @@ pub fn can_see(start: Point, end: Point, world: &World) -> bool {
     true
 }
 
+/// All the points on a line between two points, inclusive. This is Bresenham's algorithm, as in
+/// `can_see`.
+pub fn line(from: Point, to: Point) -> Vec<Point> {
+    let mut points = vec![from];
+    let mut pos = from;
+
+    let (dx, ix) = make_delta(from.x, to.x);
+    let (dy, iy) = make_delta(from.y, to.y);
+    let (dx, dy) = (dx as i32, dy as i32);
+    let inc_x = if ix { 1 } else { -1 };
+    let inc_y = if iy { 1 } else { -1 };
+
+    let mut err = dx - dy;
+    while pos != to {
+        let err2 = 2 * err;
+        if err2 > -dy {
+            err -= dy;
+            pos.x = signed_add(pos.x, inc_x);
+        }
+        if err2 < dx {
+            err += dx;
+            pos.y = signed_add(pos.y, inc_y);
+        }
+        points.push(pos);
+    }
+
+    points
+}
+
+/// Quote a string for JSON.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quote a field for CSV, if it needs it.
+pub fn csv_field(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
 /// Take the delta between two values, and return the gradient.
 fn make_delta(start: usize, end: usize) -> (usize, bool) {
     if start < end {